@@ -0,0 +1,105 @@
+//! Measure the peak stack usage of key generation, signing and verification,
+//! as listed in the README:
+//!
+//! ```sh
+//! cargo run --release --example stack_usage
+//! cargo run --release --example stack_usage --features low-memory
+//! ```
+//!
+//! The unused stack below the caller is painted with a pattern before each
+//! call, and the deepest overwritten word is searched for afterwards.
+
+extern crate rand;
+extern crate dilithium;
+
+use std::{ ptr, thread };
+use std::hint::black_box;
+use rand::{ SeedableRng, ChaChaRng };
+use dilithium::params::{ Mode, Bytes, Mode0, Mode1, Mode2, Mode3 };
+use dilithium::mldsa::{ self, MlDsa, MlDsa44, MlDsa65, MlDsa87 };
+use dilithium::round3::{ self, Round3, Dilithium2, Dilithium3, Dilithium5 };
+
+
+/// Stack painted below the caller, of the 8 MiB of the measuring thread.
+const SPAN: usize = 1 << 20;
+/// Left alone right below the caller, for `paint` and `scan` themselves.
+const SKIP: usize = 4096;
+const PATTERN: u64 = 0xdead_beef_cafe_f00d;
+
+#[inline(never)]
+fn paint(base: usize) {
+    for addr in (base - SPAN..base - SKIP).step_by(8) {
+        unsafe { ptr::write_volatile(addr as *mut u64, PATTERN) }
+    }
+}
+
+#[inline(never)]
+fn scan(base: usize) -> usize {
+    (base - SPAN..base)
+        .step_by(8)
+        .find(|&addr| unsafe { ptr::read_volatile(addr as *const u64) } != PATTERN)
+        .map_or(0, |addr| base - addr)
+}
+
+/// Peak stack usage of `f` in bytes, rounded up to 8 bytes.
+fn measure<F: FnOnce()>(f: F) -> usize {
+    let marker = 0u8;
+    let base = black_box(&marker) as *const u8 as usize & !7;
+    paint(base);
+    black_box(f)();
+    scan(base)
+}
+
+fn report(name: &str, keypair: usize, sign: usize, verify: usize) {
+    let kib = |bytes: usize| bytes.div_ceil(1024);
+    println!("| {:16} | {:7} | {:4} | {:6} |", format!("`{}`", name), kib(keypair), kib(sign), kib(verify));
+}
+
+fn round1<P: Mode>(name: &str) {
+    let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+    let mut sig = P::Signature::zero();
+
+    let keypair = measure(|| dilithium::sign::keypair_from_seed::<P>(&[1; 32], &mut pk, &mut sk));
+    let sign = measure(|| dilithium::sign::sign::<P>(&mut sig, b"message", &sk));
+    let verify = measure(|| dilithium::sign::verify::<P>(b"message", &sig, &pk).unwrap());
+    report(name, keypair, sign, verify);
+}
+
+fn fips204<P: MlDsa>(name: &str) {
+    let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+    let mut sig = P::Signature::zero();
+    let mut rng = ChaChaRng::from_seed([2; 32]);
+
+    let keypair = measure(|| mldsa::keypair_from_seed::<P>(&[1; 32], &mut pk, &mut sk));
+    let sign = measure(|| mldsa::sign::<P, _>(&mut rng, &mut sig, b"message", b"", &sk).unwrap());
+    let verify = measure(|| mldsa::verify::<P>(b"message", b"", &sig, &pk).unwrap());
+    report(name, keypair, sign, verify);
+}
+
+fn v31<P: Round3>(name: &str) {
+    let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+    let mut sig = P::Signature::zero();
+
+    let keypair = measure(|| round3::keypair_from_seed::<P>(&[1; 32], &mut pk, &mut sk));
+    let sign = measure(|| round3::sign::<P>(&mut sig, b"message", &sk));
+    let verify = measure(|| round3::verify::<P>(b"message", &sig, &pk).unwrap());
+    report(name, keypair, sign, verify);
+}
+
+fn main() {
+    let measuring = thread::Builder::new().stack_size(8 << 20).spawn(|| {
+        println!("| Parameter set    | keypair | sign | verify |");
+        println!("|------------------|--------:|-----:|-------:|");
+        round1::<Mode0>("Mode0");
+        round1::<Mode1>("Mode1");
+        round1::<Mode2>("Mode2");
+        round1::<Mode3>("Mode3");
+        fips204::<MlDsa44>("MlDsa44");
+        fips204::<MlDsa65>("MlDsa65");
+        fips204::<MlDsa87>("MlDsa87");
+        v31::<Dilithium2>("Dilithium2");
+        v31::<Dilithium3>("Dilithium3");
+        v31::<Dilithium5>("Dilithium5");
+    });
+    measuring.unwrap().join().unwrap();
+}
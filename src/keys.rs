@@ -0,0 +1,451 @@
+//! Typed keys and signatures on top of the byte oriented `sign` API.
+
+use core::fmt;
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+use rand_core::{ RngCore, CryptoRng };
+use ::params::{ Mode, Bytes, N, GAMMA1, SEEDBYTES };
+use ::polyvec::PolyVec;
+use ::packing;
+use ::sign::{ self, VerifyError, KeyError, FaultDetected };
+use ::utils::ct_eq;
+use zeroize::{ Zeroize, ZeroizeOnDrop };
+
+
+/// Error returned when decoding a key or signature from bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The input does not have the packed size of this mode.
+    InvalidLength,
+    /// The input is not a valid encoding.
+    InvalidEncoding,
+    /// The input is not a consistent secret key.
+    InvalidKey(KeyError)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidLength => f.write_str("invalid length"),
+            Error::InvalidEncoding => f.write_str("invalid encoding"),
+            Error::InvalidKey(err) => write!(f, "invalid key: {}", err)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for Error {}
+
+fn from_slice<B: Bytes>(bytes: &[u8]) -> Result<B, Error> {
+    let mut buf = B::zero();
+    if bytes.len() != buf.as_ref().len() {
+        return Err(Error::InvalidLength);
+    }
+    buf.as_mut().copy_from_slice(bytes);
+    Ok(buf)
+}
+
+
+/// Packed public key of mode `P`.
+pub struct PublicKey<P: Mode>(P::PublicKey);
+
+/// Packed secret key of mode `P`, never printed by `Debug` and wiped on drop.
+pub struct SecretKey<P: Mode>(P::SecretKey);
+
+/// Packed signature of mode `P`.
+pub struct Signature<P: Mode>(P::Signature);
+
+/// Compact secret key of mode `P`: the 32 byte seed the whole key pair is
+/// derived from, never printed by `Debug` and wiped on drop.
+///
+/// Expanding it recomputes the packed `SecretKey`. The seed can not be
+/// recovered from a packed `SecretKey`, so keep it from the start when the
+/// compact form is wanted.
+pub struct SecretSeed<P: Mode>([u8; SEEDBYTES], PhantomData<P>);
+
+/// Generate a key pair.
+pub fn keypair<P: Mode, R: RngCore + CryptoRng>(rng: &mut R) -> (PublicKey<P>, SecretKey<P>) {
+    let mut seed = [0; SEEDBYTES];
+    rng.fill_bytes(&mut seed);
+    let keypair = keypair_from_seed(&seed);
+    seed.zeroize();
+    keypair
+}
+
+/// Deterministically derive a key pair from a 32 byte seed.
+pub fn keypair_from_seed<P: Mode>(seed: &[u8; SEEDBYTES]) -> (PublicKey<P>, SecretKey<P>) {
+    let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+    sign::keypair_from_seed::<P>(seed, &mut pk, &mut sk);
+    (PublicKey(pk), SecretKey(sk))
+}
+
+/// A public key together with its secret key.
+pub struct Keypair<P: Mode> {
+    public: PublicKey<P>,
+    secret: SecretKey<P>
+}
+
+impl<P: Mode> Keypair<P> {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let (public, secret) = keypair(rng);
+        Keypair { public, secret }
+    }
+
+    pub fn from_seed(seed: &[u8; SEEDBYTES]) -> Self {
+        let (public, secret) = keypair_from_seed(seed);
+        Keypair { public, secret }
+    }
+
+    pub fn public(&self) -> &PublicKey<P> {
+        &self.public
+    }
+
+    pub fn secret(&self) -> &SecretKey<P> {
+        &self.secret
+    }
+
+    pub fn sign(&self, m: &[u8]) -> Signature<P> {
+        self.secret.sign(m)
+    }
+
+    pub fn verify(&self, m: &[u8], sig: &Signature<P>) -> Result<(), VerifyError> {
+        self.public.verify(m, sig)
+    }
+}
+
+impl<P: Mode> PublicKey<P> {
+    /// Decode a public key. Only the length is checked: every bit pattern
+    /// of `rho` and the 9 bit `t1` coefficients is a valid encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        from_slice(bytes).map(PublicKey)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+
+    pub fn verify(&self, m: &[u8], sig: &Signature<P>) -> Result<(), VerifyError> {
+        sign::verify::<P>(m, &sig.0, &self.0)
+    }
+}
+
+impl<P: Mode> SecretKey<P> {
+    /// Decode a secret key, checking it with `sign::validate_secret_key`.
+    ///
+    /// This costs about one key generation, see `from_bytes_unchecked`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let sk = from_slice(bytes)?;
+        sign::validate_secret_key::<P>(&sk).map_err(Error::InvalidKey)?;
+        Ok(SecretKey(sk))
+    }
+
+    /// Decode a secret key checking only its length, for keys from trusted
+    /// storage. A mismatched `t0` or `tr` goes unnoticed, out of range `s1`
+    /// or `s2` coefficients make the `signature` traits return an error.
+    pub fn from_bytes_unchecked(bytes: &[u8]) -> Result<Self, Error> {
+        from_slice(bytes).map(SecretKey)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+
+    /// Recover the public key, see `sign::public_key_from_secret`.
+    pub fn public_key(&self) -> Result<PublicKey<P>, KeyError> {
+        let mut pk = P::PublicKey::zero();
+        sign::public_key_from_secret::<P>(&self.0, &mut pk)?;
+        Ok(PublicKey(pk))
+    }
+
+    pub fn sign(&self, m: &[u8]) -> Signature<P> {
+        let mut sig = P::Signature::zero();
+        sign::sign::<P>(&mut sig, m, &self.0);
+        Signature(sig)
+    }
+
+    pub fn sign_randomized<R: RngCore + CryptoRng>(&self, rng: &mut R, m: &[u8]) -> Signature<P> {
+        let mut sig = P::Signature::zero();
+        sign::sign_randomized::<P, R>(rng, &mut sig, m, &self.0);
+        Signature(sig)
+    }
+
+    /// Fault hardened signing, see `sign::sign_hardened`.
+    pub fn sign_hardened(&self, m: &[u8]) -> Result<Signature<P>, FaultDetected> {
+        let mut sig = P::Signature::zero();
+        sign::sign_hardened::<P>(&mut sig, m, &self.0)?;
+        Ok(Signature(sig))
+    }
+}
+
+impl<P: Mode> SecretSeed<P> {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        // Fill in place, so no unwiped copy of the seed is left behind
+        let mut seed = SecretSeed([0; SEEDBYTES], PhantomData);
+        rng.fill_bytes(&mut seed.0);
+        seed
+    }
+
+    /// Decode a seed. Only the length is checked, any 32 bytes are a seed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != SEEDBYTES {
+            return Err(Error::InvalidLength);
+        }
+        let mut seed = SecretSeed([0; SEEDBYTES], PhantomData);
+        seed.0.copy_from_slice(bytes);
+        Ok(seed)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Expand into the key pair, the same as `keypair_from_seed`.
+    pub fn expand(&self) -> Keypair<P> {
+        Keypair::from_seed(&self.0)
+    }
+
+    /// Whether this seed expands to `sk`.
+    pub fn matches(&self, sk: &SecretKey<P>) -> bool {
+        self.expand().secret == *sk
+    }
+}
+
+impl<'a, P: Mode> From<&'a SecretSeed<P>> for SecretKey<P> {
+    fn from(seed: &'a SecretSeed<P>) -> Self {
+        keypair_from_seed(&seed.0).1
+    }
+}
+
+impl<P: Mode> Signature<P> {
+    /// Decode a signature, rejecting malformed hint and challenge encodings
+    /// and `z` coefficients of `GAMMA1` or more in absolute value, which
+    /// signing never packs.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let sig = from_slice(bytes)?;
+        let mut z = P::PolyVecL::default();
+        let mut h = P::PolyVecK::default();
+        let mut c = [0; N];
+
+        if packing::sign::unpack::<P>(&sig, &mut z, &mut h, &mut c) && !z.chknorm(GAMMA1) {
+            Ok(Signature(sig))
+        } else {
+            Err(Error::InvalidEncoding)
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+macro_rules! impl_traits {
+    ( $name:ident ) => {
+        impl<P: Mode> Clone for $name<P> {
+            fn clone(&self) -> Self {
+                $name(self.0)
+            }
+        }
+
+        impl<'a, P: Mode> TryFrom<&'a [u8]> for $name<P> {
+            type Error = Error;
+
+            fn try_from(bytes: &'a [u8]) -> Result<Self, Error> {
+                $name::from_bytes(bytes)
+            }
+        }
+
+        impl<P: Mode> AsRef<[u8]> for $name<P> {
+            fn as_ref(&self) -> &[u8] {
+                self.as_bytes()
+            }
+        }
+
+        impl<P: Mode> PartialEq for $name<P> {
+            fn eq(&self, other: &Self) -> bool {
+                ct_eq(self.as_bytes(), other.as_bytes())
+            }
+        }
+
+        impl<P: Mode> Eq for $name<P> {}
+    }
+}
+
+impl_traits!(PublicKey);
+impl_traits!(SecretKey);
+impl_traits!(Signature);
+
+impl<P: Mode> Clone for Keypair<P> {
+    fn clone(&self) -> Self {
+        Keypair { public: self.public.clone(), secret: self.secret.clone() }
+    }
+}
+
+impl<P: Mode> fmt::Debug for Keypair<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Keypair")
+            .field("public", &self.public)
+            .field("secret", &self.secret)
+            .finish()
+    }
+}
+
+impl<P: Mode> fmt::Debug for PublicKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("PublicKey").field(&self.as_bytes()).finish()
+    }
+}
+
+impl<P: Mode> fmt::Debug for SecretKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl<P: Mode> Zeroize for SecretKey<P> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<P: Mode> Drop for SecretKey<P> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<P: Mode> ZeroizeOnDrop for SecretKey<P> {}
+
+impl<P: Mode> fmt::Debug for Signature<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Signature").field(&self.as_bytes()).finish()
+    }
+}
+
+impl<P: Mode> Clone for SecretSeed<P> {
+    fn clone(&self) -> Self {
+        SecretSeed(self.0, PhantomData)
+    }
+}
+
+impl<'a, P: Mode> TryFrom<&'a [u8]> for SecretSeed<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Error> {
+        SecretSeed::from_bytes(bytes)
+    }
+}
+
+impl<P: Mode> AsRef<[u8]> for SecretSeed<P> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<P: Mode> PartialEq for SecretSeed<P> {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(self.as_bytes(), other.as_bytes())
+    }
+}
+
+impl<P: Mode> Eq for SecretSeed<P> {}
+
+impl<P: Mode> fmt::Debug for SecretSeed<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretSeed(..)")
+    }
+}
+
+impl<P: Mode> Zeroize for SecretSeed<P> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<P: Mode> Drop for SecretSeed<P> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<P: Mode> ZeroizeOnDrop for SecretSeed<P> {}
+
+
+#[cfg(feature = "signature")]
+mod traits {
+    use signature::{ self, Signer, Verifier, RandomizedSigner };
+    use signature::rand_core::CryptoRngCore;
+    use super::*;
+
+    /// Adapts a `rand_core` 0.6 RNG, as used by `signature`, to the `rand_core` of this crate.
+    struct RngCompat<'a, R: 'a>(&'a mut R);
+
+    impl<'a, R: CryptoRngCore> RngCore for RngCompat<'a, R> {
+        fn next_u32(&mut self) -> u32 {
+            self.0.next_u32()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0.next_u64()
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.0.fill_bytes(dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ::rand_core::Error> {
+            self.0.try_fill_bytes(dest)
+                .map_err(|_| ::rand_core::Error::new(::rand_core::ErrorKind::Unavailable, "rng failure"))
+        }
+    }
+
+    impl<'a, R: CryptoRngCore> CryptoRng for RngCompat<'a, R> {}
+
+    impl<P: Mode> Signer<Signature<P>> for SecretKey<P> {
+        fn try_sign(&self, msg: &[u8]) -> Result<Signature<P>, signature::Error> {
+            let mut sig = P::Signature::zero();
+            sign::sign_checked::<P>(&mut sig, msg, &self.0).map_err(|_| signature::Error::new())?;
+            Ok(Signature(sig))
+        }
+    }
+
+    impl<P: Mode> RandomizedSigner<Signature<P>> for SecretKey<P> {
+        fn try_sign_with_rng(&self, rng: &mut impl CryptoRngCore, msg: &[u8]) -> Result<Signature<P>, signature::Error> {
+            let mut sig = P::Signature::zero();
+            sign::sign_randomized_checked::<P, _>(&mut RngCompat(rng), &mut sig, msg, &self.0)
+                .map_err(|_| signature::Error::new())?;
+            Ok(Signature(sig))
+        }
+    }
+
+    impl<P: Mode> Verifier<Signature<P>> for PublicKey<P> {
+        fn verify(&self, msg: &[u8], sig: &Signature<P>) -> Result<(), signature::Error> {
+            PublicKey::verify(self, msg, sig).map_err(|_| signature::Error::new())
+        }
+    }
+
+    impl<P: Mode> Signer<Signature<P>> for Keypair<P> {
+        fn try_sign(&self, msg: &[u8]) -> Result<Signature<P>, signature::Error> {
+            self.secret.try_sign(msg)
+        }
+    }
+
+    impl<P: Mode> RandomizedSigner<Signature<P>> for Keypair<P> {
+        fn try_sign_with_rng(&self, rng: &mut impl CryptoRngCore, msg: &[u8]) -> Result<Signature<P>, signature::Error> {
+            self.secret.try_sign_with_rng(rng, msg)
+        }
+    }
+
+    impl<P: Mode> Verifier<Signature<P>> for Keypair<P> {
+        fn verify(&self, msg: &[u8], sig: &Signature<P>) -> Result<(), signature::Error> {
+            Verifier::verify(&self.public, msg, sig)
+        }
+    }
+
+    impl<P: Mode> signature::Keypair for Keypair<P> {
+        type VerifyingKey = PublicKey<P>;
+
+        fn verifying_key(&self) -> PublicKey<P> {
+            self.public.clone()
+        }
+    }
+}
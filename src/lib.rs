@@ -1,4 +1,8 @@
-extern crate core;
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::identity_op, clippy::needless_range_loop)]
+
+#[cfg(feature = "std")] extern crate core;
+#[cfg(all(test, not(feature = "std")))] extern crate std;
 
 #[macro_use] extern crate arrayref;
 extern crate rand_core;
@@ -6,6 +10,10 @@ extern crate byteorder;
 extern crate itertools;
 extern crate digest;
 extern crate sha3;
+extern crate subtle;
+extern crate zeroize;
+#[cfg(feature = "signature")] extern crate signature;
+#[cfg(feature = "rayon")] extern crate rayon;
 
 #[macro_use] mod utils;
 mod reduce;
@@ -14,8 +22,14 @@ mod ntt;
 mod poly;
 mod polyvec;
 mod packing;
+#[cfg(any(feature = "low-memory", test))] mod lowmem;
 pub mod params;
 pub mod sign;
+pub mod keys;
+#[macro_use] pub mod mldsa;
+pub mod round3;
 
 #[cfg(test)] mod test_mul;
 #[cfg(test)] mod test_vectors;
+#[cfg(test)] mod test_lowmem;
+#[cfg(test)] mod test_hardened;
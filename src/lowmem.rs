@@ -0,0 +1,246 @@
+//! Low-memory signing and verification, enabled by the `low-memory` feature.
+//!
+//! Nothing of size `K` or `L` is kept on the stack: entries of the matrix `A`
+//! and polynomials of `y` are regenerated from their seeds whenever they are
+//! needed, and `s1`, `s2`, `t0`, `t1` and `z` are unpacked one polynomial at a
+//! time from the packed keys and signature. Signatures are identical to the
+//! ones of the default path, at the cost of computing `A y` twice and
+//! sampling `y` `2K + 1` times per attempt.
+
+use digest::Input;
+use sha3::Shake256;
+use subtle::{ Choice, ConditionallySelectable, ConstantTimeEq };
+use zeroize::Zeroize;
+use ::params::{
+    Mode, N, D, GAMMA1, GAMMA2,
+    SEEDBYTES, CRHBYTES,
+    POLT0_SIZE_PACKED, POLT1_SIZE_PACKED, POLZ_SIZE_PACKED, POLW1_SIZE_PACKED
+};
+use ::poly::{ self, Poly };
+use ::reduce;
+use ::rounding;
+use ::packing;
+use ::sign::{ expand_mat_entry, challenge_from_hasher, max_nonce_attempts, VerifyError, KeyError, SignStats };
+
+
+/// Compute row `i` of `A v`, with `v[j]` in NTT domain written to `vj` by `expand_v`.
+fn mat_row<P, F>(w: &mut Poly, a: &mut Poly, vj: &mut Poly, rho: &[u8; SEEDBYTES], i: usize, mut expand_v: F)
+where
+    P: Mode,
+    F: FnMut(&mut Poly, usize)
+{
+    *w = [0; N];
+    for j in 0..P::L {
+        expand_mat_entry(a, rho, i, j);
+        expand_v(vj, j);
+        poly::pointwise_acc_invmontgomery(w, a, vj);
+    }
+    poly::reduce(w);
+}
+
+/// Compute `c * x` for `x` in normal domain and `chat` in NTT domain, in place.
+fn mul_c(x: &mut Poly, chat: &Poly) {
+    poly::ntt(x);
+    poly::pointwise_invmontgomery_assign(x, chat);
+    poly::invntt_montgomery(x);
+}
+
+pub(crate) fn sign_mu<P: Mode>(
+    sig: &mut P::Signature,
+    mu: &[u8; CRHBYTES],
+    sk: &P::SecretKey,
+    rnd: Option<&[u8; SEEDBYTES]>,
+    max_attempts: u32,
+    stats: &mut SignStats
+) -> Result<bool, KeyError> {
+    let mut nonce = 0;
+    let mut signed = false;
+    let (mut w, mut a, mut v, mut chat) = ([0; N], [0; N], [0; N], [0; N]);
+    let mut key = [0; SEEDBYTES];
+    let mut w1pack = [0; POLW1_SIZE_PACKED];
+    let max_attempts = max_attempts.min(max_nonce_attempts::<P>());
+
+    let (rho, sk_bytes) = sk.as_ref().split_at(SEEDBYTES);
+    let (key_bytes, sk_bytes) = sk_bytes.split_at(SEEDBYTES);
+    let sk_bytes = &sk_bytes[CRHBYTES..];
+    let (s1s_bytes, sk_bytes) = sk_bytes.split_at(P::POLETA_SIZE_PACKED * P::L);
+    let (s2s_bytes, t0s_bytes) = sk_bytes.split_at(P::POLETA_SIZE_PACKED * P::K);
+    let rho = array_ref!(rho, 0, SEEDBYTES);
+    let (zs_bytes, h_bytes, c_bytes) = packing::sign::split_mut::<P>(sig.as_mut());
+
+    // Reject out of range s1 and s2 before they can stall the loop
+    let mut valid = true;
+    for eta_bytes in s1s_bytes.chunks(P::POLETA_SIZE_PACKED).chain(s2s_bytes.chunks(P::POLETA_SIZE_PACKED)) {
+        valid &= poly::eta_unpack_checked::<P>(&mut a, eta_bytes);
+    }
+    if !valid {
+        a.zeroize();
+        return Err(KeyError::EtaOutOfRange);
+    }
+
+    // Hedged mode, derive the mask seed from key, fresh randomness and mu
+    key.copy_from_slice(key_bytes);
+    if let Some(rnd) = rnd {
+        shake256!(&mut key; &key, rnd, mu);
+    }
+
+    'attempt: while stats.attempts < max_attempts {
+        let base = nonce;
+        nonce += P::L as u16;
+        stats.attempts += 1;
+
+        // Compute w1 row by row and feed it to the random oracle
+        let mut hasher = Shake256::default();
+        hasher.process(mu);
+        for i in 0..P::K {
+            mat_row::<P, _>(&mut w, &mut a, &mut v, rho, i, |y, j| {
+                poly::uniform_gamma1m1(y, &key, mu, base + j as u16);
+                poly::ntt(y);
+            });
+            poly::invntt_montgomery(&mut w);
+            poly::csubq(&mut w);
+            poly::decompose(&w, &mut a, &mut v);
+            poly::w1_pack(&mut w1pack, &v);
+            hasher.process(&w1pack);
+        }
+        challenge_from_hasher(&mut chat, hasher);
+        packing::sign::pack_c(c_bytes, &chat);
+        poly::ntt(&mut chat);
+
+        // Compute z one polynomial at a time, reject if it reveals secret
+        for j in 0..P::L {
+            poly::eta_unpack::<P>(&mut a, &s1s_bytes[j * P::POLETA_SIZE_PACKED..][..P::POLETA_SIZE_PACKED]);
+            mul_c(&mut a, &chat);
+            poly::uniform_gamma1m1(&mut v, &key, mu, base + j as u16);
+            poly::add_assign(&mut a, &v);
+            poly::freeze(&mut a);
+            if poly::chknorm(&a, GAMMA1 - P::BETA) {
+                stats.z_norm += 1;
+                continue 'attempt
+            }
+
+            poly::z_pack(array_mut_ref!(zs_bytes, j * POLZ_SIZE_PACKED, POLZ_SIZE_PACKED), &a);
+        }
+
+        // Recompute w row by row, check w - cs2 and ct0 and write the hints
+        let mut k = 0;
+        for i in 0..P::K {
+            mat_row::<P, _>(&mut w, &mut a, &mut v, rho, i, |y, j| {
+                poly::uniform_gamma1m1(y, &key, mu, base + j as u16);
+                poly::ntt(y);
+            });
+            poly::invntt_montgomery(&mut w);
+            poly::csubq(&mut w);
+            poly::decompose(&w, &mut a, &mut v);
+
+            // Compute w - cs2, reject if w1 can not be computed from it
+            poly::eta_unpack::<P>(&mut a, &s2s_bytes[i * P::POLETA_SIZE_PACKED..][..P::POLETA_SIZE_PACKED]);
+            mul_c(&mut a, &chat);
+            poly::sub_assign(&mut w, &a);
+            poly::freeze(&mut w);
+            let mut w1_differs = Choice::from(0);
+            for c in 0..N {
+                let (lo, hi) = rounding::decompose(w[c]);
+                a[c] = lo;
+                w1_differs |= !hi.ct_eq(&v[c]);
+            }
+            poly::csubq(&mut a);
+            if poly::chknorm(&a, GAMMA2 - P::BETA) {
+                stats.r0_norm += 1;
+                continue 'attempt
+            }
+            if w1_differs.into() {
+                stats.w1_mismatch += 1;
+                continue 'attempt
+            }
+
+            // Compute ct0 and the hints for w1
+            poly::t0_unpack(&mut a, &t0s_bytes[i * POLT0_SIZE_PACKED..][..POLT0_SIZE_PACKED]);
+            mul_c(&mut a, &chat);
+            poly::csubq(&mut a);
+            if poly::chknorm(&a, GAMMA2) {
+                stats.ct0_norm += 1;
+                continue 'attempt
+            }
+
+            for c in 0..N {
+                let h = rounding::make_hint(w[c], reduce::csubq(w[c] + a[c]));
+                let pos = k.min(P::OMEGA - 1);
+                h_bytes[pos] = u8::conditional_select(&h_bytes[pos], &(c as u8), Choice::from(h as u8));
+                k += h as usize;
+            }
+            if k > P::OMEGA {
+                stats.hint_count += 1;
+                continue 'attempt
+            }
+            h_bytes[P::OMEGA + i] = k as u8;
+        }
+        for b in &mut h_bytes[k..P::OMEGA] {
+            *b = 0;
+        }
+
+        signed = true;
+        break
+    }
+
+    // Wipe secret intermediates
+    key.zeroize();
+    w.zeroize();
+    a.zeroize();
+    v.zeroize();
+    Ok(signed)
+}
+
+pub(crate) fn verify_mu<P: Mode>(mu: &[u8; CRHBYTES], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    let (mut w, mut a, mut v, mut chat) = ([0; N], [0; N], [0; N], [0; N]);
+    let mut w1pack = [0; POLW1_SIZE_PACKED];
+    let mut cp_bytes = [0; N / 8 + 8];
+
+    let (rho, t1s_bytes) = pk.as_ref().split_at(SEEDBYTES);
+    let rho = array_ref!(rho, 0, SEEDBYTES);
+    let (zs_bytes, h_bytes, c_bytes) = packing::sign::split::<P>(sig.as_ref());
+
+    if !packing::sign::check_h::<P>(h_bytes) || !packing::sign::unpack_c(&mut chat, c_bytes) {
+        return Err(VerifyError::MalformedSignature);
+    }
+    for j in 0..P::L {
+        poly::z_unpack(&mut v, array_ref!(zs_bytes, j * POLZ_SIZE_PACKED, POLZ_SIZE_PACKED));
+        if poly::chknorm(&v, GAMMA1 - P::BETA) { return Err(VerifyError::NormBoundExceeded) };
+    }
+
+    // Compute Az - c2^dt1 row by row, reconstruct w1 and feed it to the random oracle
+    poly::ntt(&mut chat);
+    let mut hasher = Shake256::default();
+    hasher.process(mu);
+    for i in 0..P::K {
+        mat_row::<P, _>(&mut w, &mut a, &mut v, rho, i, |z, j| {
+            poly::z_unpack(z, array_ref!(zs_bytes, j * POLZ_SIZE_PACKED, POLZ_SIZE_PACKED));
+            poly::ntt(z);
+        });
+
+        poly::t1_unpack(&mut a, array_ref!(t1s_bytes, i * POLT1_SIZE_PACKED, POLT1_SIZE_PACKED));
+        poly::shift_left(&mut a, D as u32);
+        poly::ntt(&mut a);
+        poly::pointwise_invmontgomery_assign(&mut a, &chat);
+
+        poly::sub_assign(&mut w, &a);
+        poly::reduce(&mut w);
+        poly::invntt_montgomery(&mut w);
+        poly::csubq(&mut w);
+
+        packing::sign::unpack_h::<P>(&mut a, h_bytes, i);
+        poly::use_hint(&mut v, &w, &a);
+        poly::w1_pack(&mut w1pack, &v);
+        hasher.process(&w1pack);
+    }
+
+    // Call random oracle and verify challenge
+    challenge_from_hasher(&mut a, hasher);
+    packing::sign::pack_c(&mut cp_bytes, &a);
+
+    if cp_bytes[..].ct_eq(c_bytes).into() {
+        Ok(())
+    } else {
+        Err(VerifyError::ChallengeMismatch)
+    }
+}
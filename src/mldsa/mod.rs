@@ -0,0 +1,481 @@
+//! ML-DSA, the Module-Lattice-Based Digital Signature Standard (FIPS 204).
+//!
+//! This shares the NTT and arithmetic of the round 1 scheme in `sign`,
+//! but uses the standardized parameters, sampling and encodings.
+//!
+//! Signing panics on a secret key with `s1` or `s2` coefficients outside
+//! `[-ETA, ETA]`, rather than signing with it.
+
+use rand_core::{ RngCore, CryptoRng };
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use ::params::N;
+pub use ::params::{ ContextTooLong, CONTEXT_MAX_BYTES };
+use ::polyvec::{ self, PolyVec };
+use ::poly::{ ntt, invntt_montgomery, pointwise_invmontgomery };
+use ::sign::{ VerifyError, KeyError, expect_valid_key };
+
+#[macro_use] pub mod params;
+mod rounding;
+mod poly;
+mod packing;
+pub mod prehash;
+
+pub use self::params::{ Params, MlDsa, MlDsa44, MlDsa65, MlDsa87 };
+pub use self::prehash::{ PreHash, PreHashError };
+use self::params::{ SEEDBYTES, CRHBYTES, RNDBYTES, D };
+
+
+
+pub(crate) fn expand_mat<P: Params>(mat: &mut P::Matrix, rho: &[u8; SEEDBYTES]) {
+    for i in 0..P::K {
+        for j in 0..P::L {
+            poly::uniform(&mut mat[i][j], rho, ((i << 8) + j) as u16);
+        }
+    }
+}
+
+fn challenge_seed<P: Params>(c: &mut [u8], mu: &[u8; CRHBYTES], w1: &P::PolyVecK) {
+    use digest::{ Input, ExtendableOutput, XofReader };
+    use sha3::Shake256;
+
+    let mut w1pack = [0; N * 6 / 8];
+    let w1pack = &mut w1pack[..P::POLW1_SIZE_PACKED];
+
+    let mut hasher = Shake256::default();
+    hasher.process(mu);
+    for i in 0..P::K {
+        poly::w1_pack::<P>(w1pack, &w1[i]);
+        hasher.process(w1pack);
+    }
+    hasher.xof_result().read(c);
+}
+
+/// Compute `mu = H(tr || 0 || len(ctx) || ctx || m)`.
+fn compute_mu(mu: &mut [u8; CRHBYTES], tr: &[u8], ctx: &[u8], m: &[u8]) -> Result<(), ContextTooLong> {
+    if ctx.len() > CONTEXT_MAX_BYTES {
+        return Err(ContextTooLong);
+    }
+
+    shake256!(mu; tr, &[0, ctx.len() as u8], ctx, m);
+    Ok(())
+}
+
+/// Compute `mu = H(tr || 1 || len(ctx) || ctx || OID || PH(m))`.
+fn compute_mu_prehash(mu: &mut [u8; CRHBYTES], tr: &[u8], ctx: &[u8], oid: &[u8], digest: &[u8]) -> Result<(), ContextTooLong> {
+    if ctx.len() > CONTEXT_MAX_BYTES {
+        return Err(ContextTooLong);
+    }
+
+    shake256!(mu; tr, &[1, ctx.len() as u8], ctx, oid, digest);
+    Ok(())
+}
+
+pub(crate) fn keypair_from_seeds<P: Params>(
+    pk_bytes: &mut P::PublicKey,
+    sk_bytes: &mut P::SecretKey,
+    rho: &[u8; SEEDBYTES],
+    rhoprime: &[u8; CRHBYTES],
+    key: &[u8; SEEDBYTES]
+) {
+    let mut tr = [0; 64];
+    let tr = &mut tr[..P::TRBYTES];
+    let mut mat = P::Matrix::default();
+    let mut s1 = P::PolyVecL::default();
+    let (mut s2, mut t, mut t0, mut t1) =
+        (P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default());
+
+    // Expand matrix
+    expand_mat::<P>(&mut mat, rho);
+
+    // Sample short vectors s1 and s2
+    for i in 0..P::L {
+        poly::uniform_eta::<P>(&mut s1[i], rhoprime, i as u16);
+    }
+    for i in 0..P::K {
+        poly::uniform_eta::<P>(&mut s2[i], rhoprime, (P::L + i) as u16);
+    }
+
+    // Matrix-vector multiplication
+    let mut s1hat = s1;
+    s1hat.ntt();
+    for i in 0..P::K {
+        polyvec::pointwise_acc_invmontgomery(&mut t[i], &mat[i], &s1hat);
+        invntt_montgomery(&mut t[i]);
+    }
+
+    // Add noise vector s2
+    t.add_assign(&s2);
+
+    // Extract t1 and write public key
+    t.freeze();
+    for i in 0..P::K {
+        poly::power2round(&t[i], &mut t0[i], &mut t1[i]);
+    }
+    packing::pk::pack::<P>(pk_bytes, rho, &t1);
+
+    // Compute H(rho, t1) and write secret key
+    shake256!(tr; pk_bytes.as_ref());
+    packing::sk::pack::<P>(sk_bytes, rho, key, tr, &s1, &s2, &t0);
+
+    // Wipe secret intermediates
+    s1.zeroize();
+    s1hat.zeroize();
+    s2.zeroize();
+    t.zeroize();
+    t0.zeroize();
+}
+
+pub(crate) fn sign_mu<P: Params>(
+    sig: &mut P::Signature,
+    mu: &[u8; CRHBYTES],
+    rhoprime: &[u8; CRHBYTES],
+    sk: &P::SecretKey
+) -> Result<(), KeyError> {
+    let mut nonce = 0;
+    let mut mat = P::Matrix::default();
+    let (mut s1, mut y, mut yhat, mut z) =
+        (P::PolyVecL::default(), P::PolyVecL::default(), P::PolyVecL::default(), P::PolyVecL::default());
+    let (mut s2, mut t0, mut w, mut w0, mut w1) =
+        (P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default());
+    let (mut h, mut wcs2, mut cs2, mut ct0, mut tmp) =
+        (P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default());
+    let (mut rho, mut key, mut tr) = ([0; SEEDBYTES], [0; SEEDBYTES], [0; 64]);
+    let mut ctilde = [0; 64];
+    let ctilde = &mut ctilde[..P::CTILDEBYTES];
+
+    let valid = packing::sk::unpack_checked::<P>(sk, &mut rho, &mut key, &mut tr[..P::TRBYTES], &mut s1, &mut s2, &mut t0);
+    if !valid {
+        key.zeroize();
+        s1.zeroize();
+        s2.zeroize();
+        t0.zeroize();
+        return Err(KeyError::EtaOutOfRange);
+    }
+
+    // Expand matrix and transform vectors
+    expand_mat::<P>(&mut mat, &rho);
+    s1.ntt();
+    s2.ntt();
+    t0.ntt();
+
+    // Every attempt takes `L` nonces, stop before the 16 bit nonce would wrap
+    let max_attempts = u32::from(u16::MAX) / P::L as u32;
+    let mut attempts = 0;
+
+    loop {
+        assert!(attempts < max_attempts, "signing ran out of mask nonces");
+        attempts += 1;
+
+        let mut c = [0; N];
+
+        // Sample intermediate vector
+        for i in 0..P::L {
+            poly::uniform_gamma1::<P>(&mut y[i], rhoprime, nonce);
+            nonce += 1;
+        }
+
+        // Matrix-vector multiplication
+        yhat.clone_from(&y);
+        yhat.ntt();
+        for i in 0..P::K {
+            polyvec::pointwise_acc_invmontgomery(&mut w[i], &mat[i], &yhat);
+            invntt_montgomery(&mut w[i]);
+        }
+
+        // Decompose w and call the random oracle
+        w.csubq();
+        for i in 0..P::K {
+            poly::decompose::<P>(&w[i], &mut w0[i], &mut w1[i]);
+        }
+        challenge_seed::<P>(ctilde, mu, &w1);
+        poly::challenge::<P>(&mut c, ctilde);
+
+        // Compute z, reject if it reveals secret
+        let mut chat = c;
+        ntt(&mut chat);
+        for i in 0..P::L {
+            pointwise_invmontgomery(&mut z[i], &chat, &s1[i]);
+            invntt_montgomery(&mut z[i])
+        }
+        z.add_assign(&y);
+        z.freeze();
+        if z.chknorm(P::GAMMA1 - P::BETA) { continue };
+
+        // Compute w - cs2, reject if its low bits are too large
+        for i in 0..P::K {
+            pointwise_invmontgomery(&mut cs2[i], &chat, &s2[i]);
+            invntt_montgomery(&mut cs2[i]);
+        }
+        wcs2.with_sub(&w, &cs2);
+        wcs2.freeze();
+        for i in 0..P::K {
+            poly::decompose::<P>(&wcs2[i], &mut w0[i], &mut tmp[i]);
+        }
+        w0.csubq();
+        if w0.chknorm(P::GAMMA2 - P::BETA) { continue };
+
+        // Compute hints for w1
+        for i in 0..P::K {
+            pointwise_invmontgomery(&mut ct0[i], &chat, &t0[i]);
+            invntt_montgomery(&mut ct0[i]);
+        }
+
+        ct0.csubq();
+        if ct0.chknorm(P::GAMMA2) { continue };
+
+        tmp.with_add(&wcs2, &ct0);
+        tmp.csubq();
+        let mut hint = 0;
+        for i in 0..P::K {
+            hint += poly::make_hint::<P>(&wcs2[i], &tmp[i], &mut h[i]);
+        }
+        if hint > P::OMEGA { continue };
+
+        // Write signature
+        packing::sign::pack::<P>(sig, ctilde, &z, &h);
+
+        break
+    }
+
+    // Wipe secret intermediates
+    key.zeroize();
+    s1.zeroize();
+    s2.zeroize();
+    t0.zeroize();
+    y.zeroize();
+    yhat.zeroize();
+    z.zeroize();
+    w.zeroize();
+    w0.zeroize();
+    wcs2.zeroize();
+    cs2.zeroize();
+    ct0.zeroize();
+    tmp.zeroize();
+    Ok(())
+}
+
+pub(crate) fn verify_mu<P: Params>(mu: &[u8; CRHBYTES], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    let mut rho = [0; SEEDBYTES];
+    let (mut ctilde, mut ctilde2) = ([0; 64], [0; 64]);
+    let (ctilde, ctilde2) = (&mut ctilde[..P::CTILDEBYTES], &mut ctilde2[..P::CTILDEBYTES]);
+    let mut c = [0; N];
+    let mut mat = P::Matrix::default();
+    let mut z = P::PolyVecL::default();
+    let (mut t1, mut w1, mut h) =
+        (P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default());
+    let (mut tmp1, mut tmp2) = (P::PolyVecK::default(), P::PolyVecK::default());
+
+    packing::pk::unpack::<P>(pk, &mut rho, &mut t1);
+    let r = packing::sign::unpack::<P>(sig, ctilde, &mut z, &mut h);
+
+    if !r { return Err(VerifyError::MalformedSignature) };
+    if z.chknorm(P::GAMMA1 - P::BETA) { return Err(VerifyError::NormBoundExceeded) };
+
+    poly::challenge::<P>(&mut c, ctilde);
+
+    // Matrix-vector multiplication; compute Az - c2^dt1
+    expand_mat::<P>(&mut mat, &rho);
+    z.ntt();
+    for i in 0..P::K {
+        polyvec::pointwise_acc_invmontgomery(&mut tmp1[i], &mat[i], &z);
+    }
+
+    let mut chat = c;
+    ntt(&mut chat);
+    t1.shift_left(D as u32);
+    t1.ntt();
+    for i in 0..P::K {
+        pointwise_invmontgomery(&mut tmp2[i], &chat, &t1[i]);
+    }
+
+    let mut tmp = P::PolyVecK::default();
+    tmp.with_sub(&tmp1, &tmp2);
+    tmp.reduce();
+    tmp.invntt_montgomery();
+
+    // Reconstruct w1
+    tmp.csubq();
+    for i in 0..P::K {
+        poly::use_hint::<P>(&mut w1[i], &tmp[i], &h[i]);
+    }
+
+    // Call random oracle and verify challenge
+    challenge_seed::<P>(ctilde2, mu, &w1);
+
+    if ctilde.ct_eq(ctilde2).into() {
+        Ok(())
+    } else {
+        Err(VerifyError::ChallengeMismatch)
+    }
+}
+
+/// Generate a key pair from the 32 byte seed `xi` (ML-DSA.KeyGen_internal).
+pub fn keypair_from_seed<P: MlDsa>(xi: &[u8; SEEDBYTES], pk: &mut P::PublicKey, sk: &mut P::SecretKey) {
+    let mut seedbuf = [0; 2 * SEEDBYTES + CRHBYTES];
+
+    // Expand 32 bytes of randomness into rho, rhoprime and key
+    shake256!(&mut seedbuf; xi, &[P::K as u8, P::L as u8]);
+    let rho = array_ref!(seedbuf, 0, SEEDBYTES);
+    let rhoprime = array_ref!(seedbuf, SEEDBYTES, CRHBYTES);
+    let key = array_ref!(seedbuf, SEEDBYTES + CRHBYTES, SEEDBYTES);
+
+    keypair_from_seeds::<P>(pk, sk, rho, rhoprime, key);
+    seedbuf.zeroize();
+}
+
+/// Generate a key pair (ML-DSA.KeyGen).
+pub fn keypair<P: MlDsa, R: RngCore + CryptoRng>(rng: &mut R, pk: &mut P::PublicKey, sk: &mut P::SecretKey) {
+    let mut xi = [0; SEEDBYTES];
+    rng.fill_bytes(&mut xi);
+    keypair_from_seed::<P>(&xi, pk, sk);
+    xi.zeroize();
+}
+
+fn sign_with_rnd<P: Params>(sig: &mut P::Signature, mu: &[u8; CRHBYTES], rnd: &[u8; RNDBYTES], sk: &P::SecretKey) {
+    let mut rhoprime = [0; CRHBYTES];
+    let key = &sk.as_ref()[SEEDBYTES..][..SEEDBYTES];
+
+    shake256!(&mut rhoprime; key, rnd, mu);
+    let r = sign_mu::<P>(sig, mu, &rhoprime, sk);
+    rhoprime.zeroize();
+    expect_valid_key(r);
+}
+
+fn sign_hedged<P: Params, R: RngCore + CryptoRng>(rng: &mut R, sig: &mut P::Signature, mu: &[u8; CRHBYTES], sk: &P::SecretKey) {
+    let mut rnd = [0; RNDBYTES];
+    rng.fill_bytes(&mut rnd);
+    sign_with_rnd::<P>(sig, mu, &rnd, sk);
+    rnd.zeroize();
+}
+
+fn secret_tr<P: Params>(sk: &P::SecretKey) -> &[u8] {
+    &sk.as_ref()[2 * SEEDBYTES..][..P::TRBYTES]
+}
+
+/// Hedged signing (ML-DSA.Sign), mixing 32 fresh random bytes into the mask seed.
+pub fn sign<P: MlDsa, R: RngCore + CryptoRng>(
+    rng: &mut R,
+    sig: &mut P::Signature,
+    m: &[u8],
+    ctx: &[u8],
+    sk: &P::SecretKey
+) -> Result<(), ContextTooLong> {
+    let mut mu = [0; CRHBYTES];
+    compute_mu(&mut mu, secret_tr::<P>(sk), ctx, m)?;
+    sign_hedged::<P, R>(rng, sig, &mu, sk);
+    Ok(())
+}
+
+/// Deterministic variant of ML-DSA.Sign, using an all-zero `rnd`.
+pub fn sign_deterministic<P: MlDsa>(
+    sig: &mut P::Signature,
+    m: &[u8],
+    ctx: &[u8],
+    sk: &P::SecretKey
+) -> Result<(), ContextTooLong> {
+    let mut mu = [0; CRHBYTES];
+    compute_mu(&mut mu, secret_tr::<P>(sk), ctx, m)?;
+    sign_with_rnd::<P>(sig, &mu, &[0; RNDBYTES], sk);
+    Ok(())
+}
+
+/// Hedged pre-hash signing (HashML-DSA.Sign) of a message fed to `ph`.
+///
+/// The digest is bound together with the OID of its hash function and a
+/// domain separator distinct from `sign`, so a pre-hash signature never
+/// verifies as a pure one and vice versa.
+pub fn sign_prehash<P: MlDsa, H: PreHash, R: RngCore + CryptoRng>(
+    rng: &mut R,
+    sig: &mut P::Signature,
+    ph: H,
+    ctx: &[u8],
+    sk: &P::SecretKey
+) -> Result<(), PreHashError> {
+    sign_digest::<P, R>(rng, sig, H::oid(), ph.finish().as_ref(), ctx, sk)
+}
+
+/// Deterministic variant of HashML-DSA.Sign, using an all-zero `rnd`.
+pub fn sign_prehash_deterministic<P: MlDsa, H: PreHash>(
+    sig: &mut P::Signature,
+    ph: H,
+    ctx: &[u8],
+    sk: &P::SecretKey
+) -> Result<(), PreHashError> {
+    sign_digest_deterministic::<P>(sig, H::oid(), ph.finish().as_ref(), ctx, sk)
+}
+
+/// HashML-DSA.Sign of a digest computed elsewhere, e.g. a SHA-2 digest from
+/// an upstream system, under the DER encoded `oid` of its hash function.
+///
+/// A digest whose length does not match a hash function of `prehash` named
+/// by `oid` is refused, so it can not be signed as that hash function's,
+/// and so is an `oid` that is not a single DER encoded object identifier.
+pub fn sign_digest<P: MlDsa, R: RngCore + CryptoRng>(
+    rng: &mut R,
+    sig: &mut P::Signature,
+    oid: &[u8],
+    digest: &[u8],
+    ctx: &[u8],
+    sk: &P::SecretKey
+) -> Result<(), PreHashError> {
+    prehash::check_digest(oid, digest)?;
+
+    let mut mu = [0; CRHBYTES];
+    compute_mu_prehash(&mut mu, secret_tr::<P>(sk), ctx, oid, digest)?;
+    sign_hedged::<P, R>(rng, sig, &mu, sk);
+    Ok(())
+}
+
+/// Deterministic variant of `sign_digest`, using an all-zero `rnd`.
+pub fn sign_digest_deterministic<P: MlDsa>(
+    sig: &mut P::Signature,
+    oid: &[u8],
+    digest: &[u8],
+    ctx: &[u8],
+    sk: &P::SecretKey
+) -> Result<(), PreHashError> {
+    prehash::check_digest(oid, digest)?;
+
+    let mut mu = [0; CRHBYTES];
+    compute_mu_prehash(&mut mu, secret_tr::<P>(sk), ctx, oid, digest)?;
+    sign_with_rnd::<P>(sig, &mu, &[0; RNDBYTES], sk);
+    Ok(())
+}
+
+/// Verify a signature (ML-DSA.Verify).
+pub fn verify<P: MlDsa>(m: &[u8], ctx: &[u8], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    let (mut tr, mut mu) = ([0; 64], [0; CRHBYTES]);
+    let tr = &mut tr[..P::TRBYTES];
+
+    shake256!(tr; pk.as_ref());
+    if compute_mu(&mut mu, tr, ctx, m).is_err() {
+        return Err(VerifyError::ContextTooLong);
+    }
+
+    verify_mu::<P>(&mu, sig, pk)
+}
+
+/// Verify a pre-hash signature (HashML-DSA.Verify) of a message fed to `ph`.
+pub fn verify_prehash<P: MlDsa, H: PreHash>(ph: H, ctx: &[u8], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    verify_digest::<P>(H::oid(), ph.finish().as_ref(), ctx, sig, pk)
+}
+
+/// Verify a pre-hash signature over a digest computed elsewhere, see `sign_digest`.
+pub fn verify_digest<P: MlDsa>(oid: &[u8], digest: &[u8], ctx: &[u8], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    let (mut tr, mut mu) = ([0; 64], [0; CRHBYTES]);
+    let tr = &mut tr[..P::TRBYTES];
+
+    match prehash::check_digest(oid, digest) {
+        Err(PreHashError::MalformedOid) => return Err(VerifyError::MalformedOid),
+        Err(_) => return Err(VerifyError::DigestLengthMismatch),
+        Ok(()) => ()
+    }
+
+    shake256!(tr; pk.as_ref());
+    if compute_mu_prehash(&mut mu, tr, ctx, oid, digest).is_err() {
+        return Err(VerifyError::ContextTooLong);
+    }
+
+    verify_mu::<P>(&mu, sig, pk)
+}
@@ -0,0 +1,160 @@
+use ::params::N;
+use super::params::{ Params, SEEDBYTES, POLT0_SIZE_PACKED, POLT1_SIZE_PACKED };
+use super::poly;
+
+
+pub mod pk {
+    use super::*;
+
+    pub fn pack<P: Params>(pk: &mut P::PublicKey, rho: &[u8; SEEDBYTES], t1: &P::PolyVecK) {
+        let (rho_bytes, t1s_bytes) = pk.as_mut().split_at_mut(SEEDBYTES);
+
+        rho_bytes.copy_from_slice(rho);
+        for (i, t1_bytes) in t1s_bytes.chunks_mut(POLT1_SIZE_PACKED).enumerate() {
+            poly::t1_pack(t1_bytes, &t1[i]);
+        }
+    }
+
+    pub fn unpack<P: Params>(pk: &P::PublicKey, rho: &mut [u8; SEEDBYTES], t1: &mut P::PolyVecK) {
+        let (rho_bytes, t1s_bytes) = pk.as_ref().split_at(SEEDBYTES);
+
+        rho.copy_from_slice(rho_bytes);
+        for (i, t1_bytes) in t1s_bytes.chunks(POLT1_SIZE_PACKED).enumerate() {
+            poly::t1_unpack(&mut t1[i], t1_bytes);
+        }
+    }
+}
+
+pub mod sk {
+    use super::*;
+
+    pub fn pack<P: Params>(
+        sk: &mut P::SecretKey,
+        rho: &[u8; SEEDBYTES],
+        key: &[u8; SEEDBYTES],
+        tr: &[u8],
+        s1: &P::PolyVecL,
+        s2: &P::PolyVecK,
+        t0: &P::PolyVecK
+    ) {
+        let (rho_bytes, sk) = sk.as_mut().split_at_mut(SEEDBYTES);
+        let (key_bytes, sk) = sk.split_at_mut(SEEDBYTES);
+        let (tr_bytes, sk) = sk.split_at_mut(P::TRBYTES);
+        let (s1s_bytes, sk) = sk.split_at_mut(P::POLETA_SIZE_PACKED * P::L);
+        let (s2s_bytes, t0s_bytes) = sk.split_at_mut(P::POLETA_SIZE_PACKED * P::K);
+
+        rho_bytes.copy_from_slice(rho);
+        key_bytes.copy_from_slice(key);
+        tr_bytes.copy_from_slice(tr);
+
+        for (i, s1_bytes) in s1s_bytes.chunks_mut(P::POLETA_SIZE_PACKED).enumerate() {
+            poly::eta_pack::<P>(s1_bytes, &s1[i]);
+        }
+        for (i, s2_bytes) in s2s_bytes.chunks_mut(P::POLETA_SIZE_PACKED).enumerate() {
+            poly::eta_pack::<P>(s2_bytes, &s2[i]);
+        }
+        for (i, t0_bytes) in t0s_bytes.chunks_mut(POLT0_SIZE_PACKED).enumerate() {
+            poly::t0_pack(t0_bytes, &t0[i]);
+        }
+    }
+
+    /// Unpack a secret key, returning whether all `s1` and `s2` coefficients
+    /// lie in `[-ETA, ETA]`.
+    pub fn unpack_checked<P: Params>(
+        sk: &P::SecretKey,
+        rho: &mut [u8; SEEDBYTES],
+        key: &mut [u8; SEEDBYTES],
+        tr: &mut [u8],
+        s1: &mut P::PolyVecL,
+        s2: &mut P::PolyVecK,
+        t0: &mut P::PolyVecK
+    ) -> bool {
+        let (rho_bytes, sk) = sk.as_ref().split_at(SEEDBYTES);
+        let (key_bytes, sk) = sk.split_at(SEEDBYTES);
+        let (tr_bytes, sk) = sk.split_at(P::TRBYTES);
+        let (s1s_bytes, sk) = sk.split_at(P::POLETA_SIZE_PACKED * P::L);
+        let (s2s_bytes, t0s_bytes) = sk.split_at(P::POLETA_SIZE_PACKED * P::K);
+
+        rho.copy_from_slice(rho_bytes);
+        key.copy_from_slice(key_bytes);
+        tr.copy_from_slice(tr_bytes);
+        let mut valid = true;
+
+        for (i, s1_bytes) in s1s_bytes.chunks(P::POLETA_SIZE_PACKED).enumerate() {
+            valid &= poly::eta_unpack_checked::<P>(&mut s1[i], s1_bytes);
+        }
+        for (i, s2_bytes) in s2s_bytes.chunks(P::POLETA_SIZE_PACKED).enumerate() {
+            valid &= poly::eta_unpack_checked::<P>(&mut s2[i], s2_bytes);
+        }
+        for (i, t0_bytes) in t0s_bytes.chunks(POLT0_SIZE_PACKED).enumerate() {
+            poly::t0_unpack(&mut t0[i], t0_bytes);
+        }
+
+        valid
+    }
+}
+
+pub mod sign {
+    use super::*;
+
+    pub fn pack<P: Params>(sign: &mut P::Signature, c: &[u8], z: &P::PolyVecL, h: &P::PolyVecK) {
+        let (c_bytes, sign) = sign.as_mut().split_at_mut(P::CTILDEBYTES);
+        let (zs_bytes, h_bytes) = sign.split_at_mut(P::POLZ_SIZE_PACKED * P::L);
+
+        c_bytes.copy_from_slice(c);
+
+        for (i, z_bytes) in zs_bytes.chunks_mut(P::POLZ_SIZE_PACKED).enumerate() {
+            poly::z_pack::<P>(z_bytes, &z[i]);
+        }
+
+        let mut k = 0;
+        for x in h_bytes.iter_mut() {
+            *x = 0;
+        }
+        for i in 0..P::K {
+            for j in 0..N {
+                if h[i][j] != 0 {
+                    h_bytes[k] = j as u8;
+                    k += 1;
+                }
+            }
+            h_bytes[P::OMEGA + i] = k as u8;
+        }
+    }
+
+    pub fn unpack<P: Params>(sign: &P::Signature, c: &mut [u8], z: &mut P::PolyVecL, h: &mut P::PolyVecK) -> bool {
+        let (c_bytes, sign) = sign.as_ref().split_at(P::CTILDEBYTES);
+        let (zs_bytes, h_bytes) = sign.split_at(P::POLZ_SIZE_PACKED * P::L);
+
+        c.copy_from_slice(c_bytes);
+
+        for (i, z_bytes) in zs_bytes.chunks(P::POLZ_SIZE_PACKED).enumerate() {
+            poly::z_unpack::<P>(&mut z[i], z_bytes);
+        }
+
+        // Decode h
+        let mut k = 0;
+        for i in 0..P::K {
+            if (h_bytes[P::OMEGA + i] as usize) < k || (h_bytes[P::OMEGA + i] as usize) > P::OMEGA {
+                return false;
+            }
+
+            for j in k..(h_bytes[P::OMEGA + i] as usize) {
+                // Coefficients are ordered for strong unforgeability
+                if j > k && h_bytes[j] <= h_bytes[j - 1] {
+                    return false;
+                }
+
+                h[i][h_bytes[j] as usize] = 1;
+            }
+            k = h_bytes[P::OMEGA + i] as usize;
+        }
+
+        // Extra indices are zero for strong unforgeability
+        if h_bytes[k..P::OMEGA].iter().any(|&v| v != 0) {
+            return false;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,150 @@
+use core::ops::{ Index, IndexMut };
+use ::params::{ N, Q, Bytes };
+use ::polyvec::PolyVec;
+
+pub use ::params::SEEDBYTES;
+
+pub const CRHBYTES    : usize = 64;
+pub const RNDBYTES    : usize = 32;
+pub const D           : usize = 13;
+
+pub const POLT1_SIZE_PACKED: usize = (N * (23 - D)) / 8;
+pub const POLT0_SIZE_PACKED: usize = (N * D) / 8;
+
+
+const fn bitlen(x: u32) -> usize {
+    32 - x.leading_zeros() as usize
+}
+
+/// An ML-DSA parameter set, as listed in FIPS 204 table 1.
+pub trait Params {
+    const K     : usize;
+    const L     : usize;
+    const ETA   : u32;
+    const TAU   : usize;
+    const BETA  : u32;
+    const GAMMA1: u32;
+    const GAMMA2: u32;
+    const OMEGA : usize;
+
+    /// Length of the commitment hash `c~` in bytes, `lambda / 4`.
+    const CTILDEBYTES: usize;
+    /// Length of `tr = H(pk)` in bytes.
+    const TRBYTES    : usize = 64;
+
+    const ETABITS: usize = bitlen(2 * Self::ETA);
+    const ZBITS  : usize = bitlen(2 * Self::GAMMA1 - 1);
+    const W1BITS : usize = bitlen((Q - 1) / (2 * Self::GAMMA2) - 1);
+
+    const POLETA_SIZE_PACKED: usize = (N * Self::ETABITS) / 8;
+    const POLZ_SIZE_PACKED  : usize = (N * Self::ZBITS) / 8;
+    const POLW1_SIZE_PACKED : usize = (N * Self::W1BITS) / 8;
+
+    const PK_SIZE_PACKED : usize = SEEDBYTES + Self::K * POLT1_SIZE_PACKED;
+    const SK_SIZE_PACKED : usize = 2 * SEEDBYTES + Self::TRBYTES
+        + (Self::L + Self::K) * Self::POLETA_SIZE_PACKED + Self::K * POLT0_SIZE_PACKED;
+    const SIG_SIZE_PACKED: usize = Self::CTILDEBYTES + Self::L * Self::POLZ_SIZE_PACKED + Self::OMEGA + Self::K;
+
+    const PUBLICKEYBYTES: usize = Self::PK_SIZE_PACKED;
+    const SECRETKEYBYTES: usize = Self::SK_SIZE_PACKED;
+    const BYTES         : usize = Self::SIG_SIZE_PACKED;
+
+    type PolyVecL: PolyVec;
+    type PolyVecK: PolyVec;
+    type Matrix: Copy + Default + Send + Sync + Index<usize, Output = Self::PolyVecL> + IndexMut<usize>;
+
+    type PublicKey: Bytes;
+    type SecretKey: Bytes;
+    type Signature: Bytes;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Marker for the FIPS 204 parameter sets accepted by the `mldsa` API.
+///
+/// Sealed, so the round 3 parameter sets, which share `Params` but not
+/// the key derivation or `mu`, can not be passed to `mldsa` functions.
+///
+/// ```compile_fail
+/// use dilithium::mldsa::{ self, Params };
+/// use dilithium::round3::Dilithium2;
+///
+/// let mut pk = [0; Dilithium2::PUBLICKEYBYTES];
+/// let mut sk = [0; Dilithium2::SECRETKEYBYTES];
+/// mldsa::keypair_from_seed::<Dilithium2>(&[0; 32], &mut pk, &mut sk);
+/// ```
+pub trait MlDsa: Params + private::Sealed {}
+
+macro_rules! params {
+    (
+        $( #[$attr:meta] )*
+        $name:ident {
+            K: $k:expr, L: $l:expr, ETA: $eta:expr, TAU: $tau:expr, BETA: $beta:expr,
+            GAMMA1: $gamma1:expr, GAMMA2: $gamma2:expr, OMEGA: $omega:expr,
+            CTILDEBYTES: $ctilde:expr $(, TRBYTES: $tr:expr )*
+        }
+    ) => {
+        $( #[$attr] )*
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct $name;
+
+        impl Params for $name {
+            const K     : usize = $k;
+            const L     : usize = $l;
+            const ETA   : u32   = $eta;
+            const TAU   : usize = $tau;
+            const BETA  : u32   = $beta;
+            const GAMMA1: u32   = $gamma1;
+            const GAMMA2: u32   = $gamma2;
+            const OMEGA : usize = $omega;
+
+            const CTILDEBYTES: usize = $ctilde;
+            $( const TRBYTES: usize = $tr; )*
+
+            type PolyVecL = ::polyvec::PolyArray<$l>;
+            type PolyVecK = ::polyvec::PolyArray<$k>;
+            type Matrix = [::polyvec::PolyArray<$l>; $k];
+
+            type PublicKey = [u8; <$name as Params>::PK_SIZE_PACKED];
+            type SecretKey = [u8; <$name as Params>::SK_SIZE_PACKED];
+            type Signature = [u8; <$name as Params>::SIG_SIZE_PACKED];
+        }
+    }
+}
+
+params!(
+    /// ML-DSA-44, NIST security category 2.
+    MlDsa44 {
+        K: 4, L: 4, ETA: 2, TAU: 39, BETA: 78,
+        GAMMA1: 1 << 17, GAMMA2: (Q - 1) / 88, OMEGA: 80,
+        CTILDEBYTES: 32
+    }
+);
+
+params!(
+    /// ML-DSA-65, NIST security category 3.
+    MlDsa65 {
+        K: 6, L: 5, ETA: 4, TAU: 49, BETA: 196,
+        GAMMA1: 1 << 19, GAMMA2: (Q - 1) / 32, OMEGA: 55,
+        CTILDEBYTES: 48
+    }
+);
+
+params!(
+    /// ML-DSA-87, NIST security category 5.
+    MlDsa87 {
+        K: 8, L: 7, ETA: 2, TAU: 60, BETA: 120,
+        GAMMA1: 1 << 19, GAMMA2: (Q - 1) / 32, OMEGA: 75,
+        CTILDEBYTES: 64
+    }
+);
+
+impl private::Sealed for MlDsa44 {}
+impl private::Sealed for MlDsa65 {}
+impl private::Sealed for MlDsa87 {}
+
+impl MlDsa for MlDsa44 {}
+impl MlDsa for MlDsa65 {}
+impl MlDsa for MlDsa87 {}
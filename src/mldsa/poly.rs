@@ -0,0 +1,239 @@
+use byteorder::{ ByteOrder, LittleEndian };
+use digest::{ Input, ExtendableOutput, XofReader };
+use sha3::{ Shake128, Shake256 };
+use subtle::{ Choice, ConstantTimeGreater };
+use ::params::{ N, Q };
+use ::poly::Poly;
+use super::params::{ Params, SEEDBYTES, CRHBYTES, D };
+use super::rounding;
+
+
+const SHAKE128_RATE: usize = 168;
+const SHAKE256_RATE: usize = 136;
+
+/// Pack the `bits` low bits of `f(a[i])` for every coefficient, least significant bit first.
+fn bit_pack<F: Fn(u32) -> u32>(r: &mut [u8], a: &Poly, bits: usize, f: F) {
+    let mut buf = 0u64;
+    let mut len = 0;
+    let mut pos = 0;
+
+    for &x in a.iter() {
+        buf |= u64::from(f(x)) << len;
+        len += bits;
+        while len >= 8 {
+            r[pos] = buf as u8;
+            pos += 1;
+            buf >>= 8;
+            len -= 8;
+        }
+    }
+}
+
+/// Inverse of `bit_pack`, applying `f` to every unpacked value.
+fn bit_unpack<F: Fn(u32) -> u32>(r: &mut Poly, a: &[u8], bits: usize, f: F) {
+    let mask = (1 << bits) - 1;
+    let mut buf = 0u64;
+    let mut len = 0;
+    let mut pos = 0;
+
+    for x in r.iter_mut() {
+        while len < bits {
+            buf |= u64::from(a[pos]) << len;
+            pos += 1;
+            len += 8;
+        }
+        *x = f((buf & mask) as u32);
+        buf >>= bits;
+        len -= bits;
+    }
+}
+
+/// RejNTTPoly, one entry of the matrix `A` in NTT domain.
+pub fn uniform(a: &mut Poly, rho: &[u8; SEEDBYTES], nonce: u16) {
+    let mut outbuf = [0; SHAKE128_RATE];
+    let mut nonce_bytes = [0; 2];
+    LittleEndian::write_u16(&mut nonce_bytes, nonce);
+
+    let mut hasher = Shake128::default();
+    hasher.process(rho);
+    hasher.process(&nonce_bytes);
+    let mut xof = hasher.xof_result();
+
+    let mut ctr = 0;
+    while ctr < N {
+        xof.read(&mut outbuf);
+        for buf in outbuf.chunks(3) {
+            let val = LittleEndian::read_u24(buf) & 0x7f_ffff;
+            if val < Q && ctr < N {
+                a[ctr] = val;
+                ctr += 1;
+            }
+        }
+    }
+}
+
+/// RejBoundedPoly, coefficients in `[-ETA, ETA]` stored as `Q + x`.
+pub fn uniform_eta<P: Params>(a: &mut Poly, seed: &[u8; CRHBYTES], nonce: u16) {
+    fn coeff<P: Params>(b: u32) -> Option<u32> {
+        if P::ETA == 2 && b < 15 {
+            Some(Q + 2 - (b % 5))
+        } else if P::ETA == 4 && b < 9 {
+            Some(Q + 4 - b)
+        } else {
+            None
+        }
+    }
+
+    let mut outbuf = [0; SHAKE256_RATE];
+    let mut nonce_bytes = [0; 2];
+    LittleEndian::write_u16(&mut nonce_bytes, nonce);
+
+    let mut hasher = Shake256::default();
+    hasher.process(seed);
+    hasher.process(&nonce_bytes);
+    let mut xof = hasher.xof_result();
+
+    let mut ctr = 0;
+    while ctr < N {
+        xof.read(&mut outbuf);
+        for &b in outbuf.iter() {
+            for t in [u32::from(b & 0x0f), u32::from(b >> 4)].iter() {
+                if let Some(x) = coeff::<P>(*t) {
+                    if ctr < N {
+                        a[ctr] = x;
+                        ctr += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// ExpandMask for a single polynomial, coefficients in `(-GAMMA1, GAMMA1]` reduced into `[0, Q)`.
+pub fn uniform_gamma1<P: Params>(a: &mut Poly, seed: &[u8; CRHBYTES], nonce: u16) {
+    let mut outbuf = [0; 5 * SHAKE256_RATE];
+    let outbuf = &mut outbuf[..P::POLZ_SIZE_PACKED];
+    let mut nonce_bytes = [0; 2];
+    LittleEndian::write_u16(&mut nonce_bytes, nonce);
+
+    shake256!(outbuf; seed, &nonce_bytes);
+    z_unpack::<P>(a, outbuf);
+}
+
+/// SampleInBall, `TAU` coefficients set to `1` or `Q - 1`.
+pub fn challenge<P: Params>(c: &mut Poly, seed: &[u8]) {
+    let mut outbuf = [0; SHAKE256_RATE];
+
+    let mut hasher = Shake256::default();
+    hasher.process(seed);
+    let mut xof = hasher.xof_result();
+    xof.read(&mut outbuf);
+
+    let signs = LittleEndian::read_u64(&outbuf);
+    let mut pos = 8;
+
+    for x in c.iter_mut() {
+        *x = 0;
+    }
+
+    for (k, i) in ((N - P::TAU)..N).enumerate() {
+        let b = loop {
+            if pos >= SHAKE256_RATE {
+                xof.read(&mut outbuf);
+                pos = 0;
+            }
+
+            let b = outbuf[pos] as usize;
+            pos += 1;
+            if b <= i { break b }
+        };
+
+        c[i] = c[b];
+        c[b] = if (signs >> k) & 1 != 0 { Q - 1 } else { 1 };
+    }
+}
+
+pub fn power2round(a: &Poly, a0: &mut Poly, a1: &mut Poly) {
+    for i in 0..N {
+        let (x, y) = rounding::power2round(a[i]);
+        a0[i] = x;
+        a1[i] = y;
+    }
+}
+
+pub fn decompose<P: Params>(a: &Poly, a0: &mut Poly, a1: &mut Poly) {
+    for i in 0..N {
+        let (x, y) = rounding::decompose(a[i], P::GAMMA2);
+        a0[i] = x;
+        a1[i] = y;
+    }
+}
+
+pub fn make_hint<P: Params>(a: &Poly, b: &Poly, h: &mut Poly) -> usize {
+    let mut s = 0;
+
+    for i in 0..N {
+        h[i] = rounding::make_hint(a[i], b[i], P::GAMMA2);
+        s += h[i] as usize;
+    }
+
+    s
+}
+
+pub fn use_hint<P: Params>(a: &mut Poly, b: &Poly, h: &Poly) {
+    for i in 0..N {
+        a[i] = rounding::use_hint(b[i], h[i], P::GAMMA2);
+    }
+}
+
+pub fn eta_pack<P: Params>(r: &mut [u8], a: &Poly) {
+    bit_pack(r, a, P::ETABITS, |x| Q + P::ETA - x);
+}
+
+pub fn eta_unpack<P: Params>(r: &mut Poly, a: &[u8]) {
+    bit_unpack(r, a, P::ETABITS, |x| (Q + P::ETA).wrapping_sub(x));
+}
+
+/// `eta_unpack` that also checks every coefficient lies in `[-ETA, ETA]`.
+pub fn eta_unpack_checked<P: Params>(r: &mut Poly, a: &[u8]) -> bool {
+    eta_unpack::<P>(r, a);
+
+    // The packed values are `ETA - r`, valid ones are at most `2 ETA`
+    let invalid = r.iter()
+        .fold(Choice::from(0), |acc, &x| acc | (Q + P::ETA - x).ct_gt(&(2 * P::ETA)));
+    !bool::from(invalid)
+}
+
+pub fn t0_pack(r: &mut [u8], a: &Poly) {
+    bit_pack(r, a, D, |x| Q + (1 << (D - 1)) - x);
+}
+
+pub fn t0_unpack(r: &mut Poly, a: &[u8]) {
+    bit_unpack(r, a, D, |x| Q + (1 << (D - 1)) - x);
+}
+
+pub fn t1_pack(r: &mut [u8], a: &Poly) {
+    bit_pack(r, a, 23 - D, |x| x);
+}
+
+pub fn t1_unpack(r: &mut Poly, a: &[u8]) {
+    bit_unpack(r, a, 23 - D, |x| x);
+}
+
+pub fn z_pack<P: Params>(r: &mut [u8], a: &Poly) {
+    bit_pack(r, a, P::ZBITS, |x| {
+        let t = P::GAMMA1.wrapping_sub(x);
+        t.wrapping_add(((t as i32) >> 31) as u32 & Q)
+    });
+}
+
+pub fn z_unpack<P: Params>(r: &mut Poly, a: &[u8]) {
+    bit_unpack(r, a, P::ZBITS, |x| {
+        let t = P::GAMMA1.wrapping_sub(x);
+        t.wrapping_add(((t as i32) >> 31) as u32 & Q)
+    });
+}
+
+pub fn w1_pack<P: Params>(r: &mut [u8], a: &Poly) {
+    bit_pack(r, a, P::W1BITS, |x| x);
+}
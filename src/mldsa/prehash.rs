@@ -0,0 +1,148 @@
+//! Hash functions for the pre-hash signing mode (HashML-DSA).
+//!
+//! Pre-hashing is specific to FIPS 204, the round 1 `sign` and `round3`
+//! APIs have no equivalent.
+
+use core::fmt;
+use digest::{ Digest, Input, ExtendableOutput, XofReader };
+use sha3::{ Sha3_256, Sha3_512, Shake128, Shake256 };
+use ::params::ContextTooLong;
+
+
+/// DER encodings of the object identifiers of the hash functions FIPS 204
+/// approves, `2.16.840.1.101.3.4.2.x`, for digests computed elsewhere.
+pub const OID_SHA256  : &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+pub const OID_SHA512  : &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+pub const OID_SHA3_256: &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x08];
+pub const OID_SHA3_512: &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x0a];
+pub const OID_SHAKE128: &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x0b];
+pub const OID_SHAKE256: &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x0c];
+
+/// Reason pre-hash signing was refused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PreHashError {
+    /// The context string is longer than `CONTEXT_MAX_BYTES`.
+    ContextTooLong,
+    /// The digest length does not match the hash function named by its OID.
+    DigestLengthMismatch,
+    /// The OID is not a single DER encoded object identifier.
+    MalformedOid
+}
+
+impl From<ContextTooLong> for PreHashError {
+    fn from(_: ContextTooLong) -> PreHashError {
+        PreHashError::ContextTooLong
+    }
+}
+
+impl fmt::Display for PreHashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PreHashError::ContextTooLong => f.write_str("context string longer than 255 bytes"),
+            PreHashError::DigestLengthMismatch => f.write_str("digest length does not match its hash function"),
+            PreHashError::MalformedOid => f.write_str("malformed hash function OID")
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for PreHashError {}
+
+/// Output length in bytes of the hash function with DER encoded `oid`,
+/// if it is one of the OIDs above.
+pub fn digest_len(oid: &[u8]) -> Option<usize> {
+    match oid {
+        o if o == OID_SHA256 || o == OID_SHA3_256 || o == OID_SHAKE128 => Some(32),
+        o if o == OID_SHA512 || o == OID_SHA3_512 || o == OID_SHAKE256 => Some(64),
+        _ => None
+    }
+}
+
+/// Check that `digest` has the output length of the hash function named by
+/// `oid`.
+///
+/// `mu` absorbs `oid || digest` without framing, so other OIDs must be a
+/// single well-formed DER object identifier, tag `0x06` and a short length
+/// covering the rest, for the split between them to be unambiguous.
+pub(crate) fn check_digest(oid: &[u8], digest: &[u8]) -> Result<(), PreHashError> {
+    match digest_len(oid) {
+        Some(len) if len == digest.len() => Ok(()),
+        Some(_) => Err(PreHashError::DigestLengthMismatch),
+        None if oid.len() > 2 && oid[0] == 0x06 && oid[1] < 0x80 && usize::from(oid[1]) == oid.len() - 2 => Ok(()),
+        None => Err(PreHashError::MalformedOid)
+    }
+}
+
+/// A hash function for pre-hash signing, fed with the message through
+/// `Input` before it is passed to `sign_prehash` or `verify_prehash`.
+///
+/// Implemented for the SHA-3 and SHAKE hashers of the `sha3` crate. Other
+/// hash functions can be wrapped in a type implementing it, as long as
+/// their OID is unique to them and DER encoded.
+pub trait PreHash: Input + Default {
+    type Output: AsRef<[u8]>;
+
+    /// DER encoding of the object identifier of the hash function.
+    fn oid() -> &'static [u8];
+
+    /// Finish hashing into the digest that gets signed.
+    fn finish(self) -> Self::Output;
+}
+
+impl PreHash for Sha3_256 {
+    type Output = [u8; 32];
+
+    fn oid() -> &'static [u8] {
+        OID_SHA3_256
+    }
+
+    fn finish(self) -> [u8; 32] {
+        let mut out = [0; 32];
+        out.copy_from_slice(&self.result());
+        out
+    }
+}
+
+impl PreHash for Sha3_512 {
+    type Output = [u8; 64];
+
+    fn oid() -> &'static [u8] {
+        OID_SHA3_512
+    }
+
+    fn finish(self) -> [u8; 64] {
+        let mut out = [0; 64];
+        out.copy_from_slice(&self.result());
+        out
+    }
+}
+
+/// SHAKE128 with 256 bits of output.
+impl PreHash for Shake128 {
+    type Output = [u8; 32];
+
+    fn oid() -> &'static [u8] {
+        OID_SHAKE128
+    }
+
+    fn finish(self) -> [u8; 32] {
+        let mut out = [0; 32];
+        self.xof_result().read(&mut out);
+        out
+    }
+}
+
+/// SHAKE256 with 512 bits of output.
+impl PreHash for Shake256 {
+    type Output = [u8; 64];
+
+    fn oid() -> &'static [u8] {
+        OID_SHAKE256
+    }
+
+    fn finish(self) -> [u8; 64] {
+        let mut out = [0; 64];
+        self.xof_result().read(&mut out);
+        out
+    }
+}
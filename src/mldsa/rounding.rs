@@ -0,0 +1,47 @@
+use subtle::{ ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater };
+use ::params::Q;
+use ::utils::ct_is_negative;
+use super::params::D;
+
+
+/// Split `a` in `[0, Q)` into `(Q + a0, a1)` with `a = a1 * 2^D + a0`
+/// and `-2^(D-1) < a0 <= 2^(D-1)`.
+pub fn power2round(a: u32) -> (u32, u32) {
+    let a1 = (a + (1 << (D - 1)) - 1) >> D;
+    let a0 = a.wrapping_sub(a1 << D);
+    (Q.wrapping_add(a0), a1)
+}
+
+/// Split `a` in `[0, Q)` into `(Q + a0, a1)` with `a = a1 * 2 * gamma2 + a0`
+/// and `-gamma2 < a0 <= gamma2`, except for the corner case `a1 = 0, a0 = -gamma2`.
+pub fn decompose(a: u32, gamma2: u32) -> (u32, u32) {
+    let mut a1 = ((a + 127) >> 7) as i32;
+    if gamma2 == (Q - 1) / 32 {
+        a1 = (a1 * 1025 + (1 << 21)) >> 22;
+        a1 &= 15;
+    } else {
+        a1 = (a1 * 11275 + (1 << 23)) >> 24;
+        a1 = i32::conditional_select(&a1, &0, ct_is_negative(43 - a1));
+    }
+
+    let mut a0 = a as i32 - a1 * 2 * gamma2 as i32;
+    a0 = i32::conditional_select(&a0, &(a0 - Q as i32), ct_is_negative(((Q - 1) / 2) as i32 - a0));
+
+    (Q.wrapping_add(a0 as u32), a1 as u32)
+}
+
+pub fn make_hint(a: u32, b: u32, gamma2: u32) -> u32 {
+    let (_, x) = decompose(a, gamma2);
+    let (_, y) = decompose(b, gamma2);
+    u32::from((!x.ct_eq(&y)).unwrap_u8())
+}
+
+pub fn use_hint(a: u32, hint: u32, gamma2: u32) -> u32 {
+    let m = (Q - 1) / (2 * gamma2);
+    let (a0, a1) = decompose(a, gamma2);
+
+    let up = u32::conditional_select(&(a1 + 1), &0, a1.ct_eq(&(m - 1)));
+    let down = u32::conditional_select(&a1.wrapping_sub(1), &(m - 1), a1.ct_eq(&0));
+    let r = u32::conditional_select(&down, &up, a0.ct_gt(&Q));
+    u32::conditional_select(&r, &a1, hint.ct_eq(&0))
+}
@@ -1,32 +1,32 @@
 use ::params::{
-    L, K, N, Q, OMEGA,
+    Mode, N, Q,
     SEEDBYTES, CRHBYTES,
-    POLETA_SIZE_PACKED, POLT0_SIZE_PACKED, POLT1_SIZE_PACKED, POLZ_SIZE_PACKED,
-    PK_SIZE_PACKED, SK_SIZE_PACKED, SIG_SIZE_PACKED
+    POLT0_SIZE_PACKED, POLT1_SIZE_PACKED, POLZ_SIZE_PACKED
 };
 use ::poly::{ self, Poly };
-use ::polyvec::{ PolyVecL, PolyVecK };
 
 
 pub mod pk {
     use super::*;
 
-    pub fn pack(pk: &mut [u8; PK_SIZE_PACKED], rho: &[u8; SEEDBYTES], t1: &PolyVecK) {
-        let (rho_bytes, t1s_bytes) = mut_array_refs!(pk, SEEDBYTES, POLT1_SIZE_PACKED * K);
+    pub fn pack<P: Mode>(pk: &mut P::PublicKey, rho: &[u8; SEEDBYTES], t1: &P::PolyVecK) {
+        let (rho_bytes, t1s_bytes) = pk.as_mut().split_at_mut(SEEDBYTES);
 
-        rho_bytes.clone_from(rho);
-        for i in 0..K {
-            let t1_bytes = array_mut_ref!(t1s_bytes, i * POLT1_SIZE_PACKED, POLT1_SIZE_PACKED);
+        rho_bytes.copy_from_slice(rho);
+        for (i, t1_bytes) in t1s_bytes.chunks_mut(POLT1_SIZE_PACKED).enumerate() {
+            let t1_bytes = array_mut_ref!(t1_bytes, 0, POLT1_SIZE_PACKED);
             poly::t1_pack(t1_bytes, &t1[i]);
         }
     }
 
-    pub fn unpack(pk: &[u8; PK_SIZE_PACKED], rho: &mut [u8; SEEDBYTES], t1: &mut PolyVecK) {
-        let (rho_bytes, t1s_bytes) = array_refs!(pk, SEEDBYTES, POLT1_SIZE_PACKED * K);
+    /// Every 9 bit value is a canonical `t1` coefficient, as `power2round`
+    /// of a value below `Q` is at most `2^9 - 1`, so this can not fail.
+    pub fn unpack<P: Mode>(pk: &P::PublicKey, rho: &mut [u8; SEEDBYTES], t1: &mut P::PolyVecK) {
+        let (rho_bytes, t1s_bytes) = pk.as_ref().split_at(SEEDBYTES);
 
-        rho.clone_from(rho_bytes);
-        for i in 0..K {
-            let t1_bytes = array_ref!(t1s_bytes, i * POLT1_SIZE_PACKED, POLT1_SIZE_PACKED);
+        rho.copy_from_slice(rho_bytes);
+        for (i, t1_bytes) in t1s_bytes.chunks(POLT1_SIZE_PACKED).enumerate() {
+            let t1_bytes = array_ref!(t1_bytes, 0, POLT1_SIZE_PACKED);
             poly::t1_unpack(&mut t1[i], t1_bytes);
         }
     }
@@ -35,110 +35,122 @@ pub mod pk {
 pub mod sk {
     use super::*;
 
-    pub fn pack(
-        sk: &mut [u8; SK_SIZE_PACKED],
+    pub fn pack<P: Mode>(
+        sk: &mut P::SecretKey,
         rho: &[u8; SEEDBYTES],
         key: &[u8; SEEDBYTES],
         tr: &[u8; CRHBYTES],
-        s1: &PolyVecL,
-        s2: &PolyVecK,
-        t0: &PolyVecK
+        s1: &P::PolyVecL,
+        s2: &P::PolyVecK,
+        t0: &P::PolyVecK
     ) {
-        let (rho_bytes, key_bytes, tr_bytes, s1s_bytes, s2s_bytes, t0s_bytes) =
-            mut_array_refs!(
-                sk,
-                SEEDBYTES, SEEDBYTES, CRHBYTES,
-                POLETA_SIZE_PACKED * L,
-                POLETA_SIZE_PACKED * K,
-                POLT0_SIZE_PACKED * K
-            );
-
-        rho_bytes.clone_from(rho);
-        key_bytes.clone_from(key);
-        tr_bytes.clone_from(tr);
-
-        for i in 0..L {
-            let s1_bytes = array_mut_ref!(s1s_bytes, i * POLETA_SIZE_PACKED, POLETA_SIZE_PACKED);
-            poly::eta_pack(s1_bytes, &s1[i]);
-        }
-        for i in 0..K {
-            let s2_bytes = array_mut_ref!(s2s_bytes, i * POLETA_SIZE_PACKED, POLETA_SIZE_PACKED);
-            poly::eta_pack(s2_bytes, &s2[i]);
-        }
-        for i in 0..K {
-            let t0_bytes = array_mut_ref!(t0s_bytes, i * POLT0_SIZE_PACKED, POLT0_SIZE_PACKED);
+        let (rho_bytes, sk) = sk.as_mut().split_at_mut(SEEDBYTES);
+        let (key_bytes, sk) = sk.split_at_mut(SEEDBYTES);
+        let (tr_bytes, sk) = sk.split_at_mut(CRHBYTES);
+        let (s1s_bytes, sk) = sk.split_at_mut(P::POLETA_SIZE_PACKED * P::L);
+        let (s2s_bytes, t0s_bytes) = sk.split_at_mut(P::POLETA_SIZE_PACKED * P::K);
+
+        rho_bytes.copy_from_slice(rho);
+        key_bytes.copy_from_slice(key);
+        tr_bytes.copy_from_slice(tr);
+
+        for (i, s1_bytes) in s1s_bytes.chunks_mut(P::POLETA_SIZE_PACKED).enumerate() {
+            poly::eta_pack::<P>(s1_bytes, &s1[i]);
+        }
+        for (i, s2_bytes) in s2s_bytes.chunks_mut(P::POLETA_SIZE_PACKED).enumerate() {
+            poly::eta_pack::<P>(s2_bytes, &s2[i]);
+        }
+        for (i, t0_bytes) in t0s_bytes.chunks_mut(POLT0_SIZE_PACKED).enumerate() {
             poly::t0_pack(t0_bytes, &t0[i]);
         }
     }
 
-    pub fn unpack(
-        sk: &[u8; SK_SIZE_PACKED],
+    /// Unpack a secret key, returning whether all `s1` and `s2` coefficients
+    /// lie in `[-ETA, ETA]`.
+    ///
+    /// Every bit pattern of `t0` decodes to a value in `(-2^(D-1), 2^(D-1)]`,
+    /// so it can only be checked against `s1` and `s2`, see
+    /// `sign::validate_secret_key`.
+    pub fn unpack_checked<P: Mode>(
+        sk: &P::SecretKey,
         rho: &mut [u8; SEEDBYTES],
         key: &mut [u8; SEEDBYTES],
         tr: &mut [u8; CRHBYTES],
-        s1: &mut PolyVecL,
-        s2: &mut PolyVecK,
-        t0: &mut PolyVecK
-   ) {
-        let (rho_bytes, key_bytes, tr_bytes, s1s_bytes, s2s_bytes, t0s_bytes) =
-            array_refs!(
-                sk,
-                SEEDBYTES, SEEDBYTES, CRHBYTES,
-                POLETA_SIZE_PACKED * L,
-                POLETA_SIZE_PACKED * K,
-                POLT0_SIZE_PACKED * K
-            );
-
-        rho.clone_from(rho_bytes);
-        key.clone_from(key_bytes);
-        tr.clone_from(tr_bytes);
-
-        for i in 0..L {
-            let s1_bytes = array_ref!(s1s_bytes, i * POLETA_SIZE_PACKED, POLETA_SIZE_PACKED);
-            poly::eta_unpack(&mut s1[i], s1_bytes);
-        }
-        for i in 0..K {
-            let s2_bytes = array_ref!(s2s_bytes, i * POLETA_SIZE_PACKED, POLETA_SIZE_PACKED);
-            poly::eta_unpack(&mut s2[i], s2_bytes);
-        }
-        for i in 0..K {
-            let t0_bytes = array_ref!(t0s_bytes, i * POLT0_SIZE_PACKED, POLT0_SIZE_PACKED);
+        s1: &mut P::PolyVecL,
+        s2: &mut P::PolyVecK,
+        t0: &mut P::PolyVecK
+   ) -> bool {
+        let (rho_bytes, sk) = sk.as_ref().split_at(SEEDBYTES);
+        let (key_bytes, sk) = sk.split_at(SEEDBYTES);
+        let (tr_bytes, sk) = sk.split_at(CRHBYTES);
+        let (s1s_bytes, sk) = sk.split_at(P::POLETA_SIZE_PACKED * P::L);
+        let (s2s_bytes, t0s_bytes) = sk.split_at(P::POLETA_SIZE_PACKED * P::K);
+        let mut valid = true;
+
+        rho.copy_from_slice(rho_bytes);
+        key.copy_from_slice(key_bytes);
+        tr.copy_from_slice(tr_bytes);
+
+        for (i, s1_bytes) in s1s_bytes.chunks(P::POLETA_SIZE_PACKED).enumerate() {
+            valid &= poly::eta_unpack_checked::<P>(&mut s1[i], s1_bytes);
+        }
+        for (i, s2_bytes) in s2s_bytes.chunks(P::POLETA_SIZE_PACKED).enumerate() {
+            valid &= poly::eta_unpack_checked::<P>(&mut s2[i], s2_bytes);
+        }
+        for (i, t0_bytes) in t0s_bytes.chunks(POLT0_SIZE_PACKED).enumerate() {
             poly::t0_unpack(&mut t0[i], t0_bytes);
         }
+
+        valid
     }
 }
 
 pub mod sign {
     use super::*;
 
-    pub fn pack(sign: &mut [u8; SIG_SIZE_PACKED], z: &PolyVecL, h: &PolyVecK,c: &Poly) {
-        let (zs_bytes, h_bytes, c_bytes) =
-            mut_array_refs!(
-                sign,
-                POLZ_SIZE_PACKED * L,
-                OMEGA + K,
-                N / 8 + 8
-            );
-
-        for i in 0..L {
-            let z_bytes = array_mut_ref!(zs_bytes, i * POLZ_SIZE_PACKED, POLZ_SIZE_PACKED);
+    /// Split a packed signature into its `z`, `h` and `c` parts.
+    pub fn split<P: Mode>(sign: &[u8]) -> (&[u8], &[u8], &[u8]) {
+        let (zs_bytes, sign) = sign.split_at(POLZ_SIZE_PACKED * P::L);
+        let (h_bytes, c_bytes) = sign.split_at(P::OMEGA + P::K);
+        (zs_bytes, h_bytes, c_bytes)
+    }
+
+    pub fn split_mut<P: Mode>(sign: &mut [u8]) -> (&mut [u8], &mut [u8], &mut [u8]) {
+        let (zs_bytes, sign) = sign.split_at_mut(POLZ_SIZE_PACKED * P::L);
+        let (h_bytes, c_bytes) = sign.split_at_mut(P::OMEGA + P::K);
+        (zs_bytes, h_bytes, c_bytes)
+    }
+
+    pub fn pack<P: Mode>(sign: &mut P::Signature, z: &P::PolyVecL, h: &P::PolyVecK, c: &Poly) {
+        let (zs_bytes, h_bytes, c_bytes) = split_mut::<P>(sign.as_mut());
+
+        for (i, z_bytes) in zs_bytes.chunks_mut(POLZ_SIZE_PACKED).enumerate() {
+            let z_bytes = array_mut_ref!(z_bytes, 0, POLZ_SIZE_PACKED);
             poly::z_pack(z_bytes, &z[i]);
         }
 
         let mut k = 0;
-        for i in 0..K {
+        for i in 0..P::K {
             for j in 0..N {
                 if h[i][j] != 0 {
                     h_bytes[k] = j as u8;
                     k += 1;
                 }
             }
-            h_bytes[OMEGA + i] = k as u8;
+            h_bytes[P::OMEGA + i] = k as u8;
         }
+        for b in &mut h_bytes[k..P::OMEGA] {
+            *b = 0;
+        }
+
+        pack_c(c_bytes, c);
+    }
 
+    pub fn pack_c(c_bytes: &mut [u8], c: &Poly) {
         let mut signs: u64 = 0;
         let mut mask = 1;
         for i in 0..(N / 8) {
+            c_bytes[i] = 0;
             for j in 0..8 {
                 if c[8 * i + j] != 0 {
                     c_bytes[i] |= 1 << j;
@@ -154,43 +166,57 @@ pub mod sign {
         }
     }
 
-    pub fn unpack(sign: &[u8; SIG_SIZE_PACKED], z: &mut PolyVecL, h: &mut PolyVecK, c: &mut Poly) -> bool {
-        let (zs_bytes, h_bytes, c_bytes) =
-            array_refs!(
-                sign,
-                POLZ_SIZE_PACKED * L,
-                OMEGA + K,
-                N / 8 + 8
-            );
-
-        for i in 0..L {
-            let z_bytes = array_ref!(zs_bytes, i * POLZ_SIZE_PACKED, POLZ_SIZE_PACKED);
+    pub fn unpack<P: Mode>(sign: &P::Signature, z: &mut P::PolyVecL, h: &mut P::PolyVecK, c: &mut Poly) -> bool {
+        let (zs_bytes, h_bytes, c_bytes) = split::<P>(sign.as_ref());
+
+        for (i, z_bytes) in zs_bytes.chunks(POLZ_SIZE_PACKED).enumerate() {
+            let z_bytes = array_ref!(z_bytes, 0, POLZ_SIZE_PACKED);
             poly::z_unpack(&mut z[i], z_bytes);
         }
 
-        // Decode h
+        if !check_h::<P>(h_bytes) {
+            return false;
+        }
+        for i in 0..P::K {
+            unpack_h::<P>(&mut h[i], h_bytes, i);
+        }
+
+        unpack_c(c, c_bytes)
+    }
+
+    /// Check the hint encoding, without decoding it.
+    pub fn check_h<P: Mode>(h_bytes: &[u8]) -> bool {
         let mut k = 0;
-        for i in 0..K {
-            if (h_bytes[OMEGA + i] as usize) < k || (h_bytes[OMEGA + i] as usize) > OMEGA {
+        for i in 0..P::K {
+            if (h_bytes[P::OMEGA + i] as usize) < k || (h_bytes[P::OMEGA + i] as usize) > P::OMEGA {
                 return false;
             }
 
-            for j in k..(h_bytes[OMEGA + i] as usize) {
+            for j in k..(h_bytes[P::OMEGA + i] as usize) {
                 // Coefficients are ordered for strong unforgeability
                 if j > k && h_bytes[j] <= h_bytes[j - 1] {
                     return false;
                 }
-
-                h[i][h_bytes[j] as usize] = 1;
             }
-            k = h_bytes[OMEGA + i] as usize;
+            k = h_bytes[P::OMEGA + i] as usize;
         }
 
         // Extra indices are zero for strong unforgeability
-        if h_bytes[k..OMEGA].iter().any(|&v| v != 0) {
-            return false;
+        h_bytes[k..P::OMEGA].iter().all(|&v| v == 0)
+    }
+
+    /// Decode the `i`-th hint polynomial of an encoding accepted by `check_h`.
+    pub fn unpack_h<P: Mode>(h: &mut Poly, h_bytes: &[u8], i: usize) {
+        let start = if i == 0 { 0 } else { h_bytes[P::OMEGA + i - 1] as usize };
+        let end = h_bytes[P::OMEGA + i] as usize;
+
+        *h = [0; N];
+        for &j in &h_bytes[start..end] {
+            h[j as usize] = 1;
         }
+    }
 
+    pub fn unpack_c(c: &mut Poly, c_bytes: &[u8]) -> bool {
         let signs = (0..8)
             .map(|i| u64::from(c_bytes[N / 8 + i]) << (8 * i))
             .fold(0, |sum, next| sum | next);
@@ -200,6 +226,7 @@ pub mod sign {
             return false;
         }
 
+        *c = [0; N];
         let mut mask = 1;
         for i in 0..(N / 8) {
             for j in 0..8 {
@@ -1,4 +1,9 @@
-#![cfg_attr(feature = "cargo-clippy", allow(unreadable_literal))]
+#![allow(clippy::unreadable_literal)]
+
+use core::fmt;
+use core::ops::{ Index, IndexMut };
+use zeroize::Zeroize;
+pub use ::polyvec::{ PolyVec, PolyArray };
 
 pub const SEEDBYTES    : usize = 32;
 pub const CRHBYTES     : usize = 48;
@@ -11,65 +16,124 @@ pub const GAMMA1       : u32   = (Q - 1) / 16;
 pub const GAMMA2       : u32   = GAMMA1 / 2;
 pub const ALPHA        : u32   = 2 * GAMMA2;
 
-#[cfg(feature = "mode0")]
-mod mode {
-    pub const K       : usize = 3;
-    pub const L       : usize = 2;
-    pub const ETA     : u32   = 7;
-    pub const SETABITS: usize = 4;
-    pub const BETA    : u32   = 375;
-    pub const OMEGA   : usize = 64;
+pub const POL_SIZE_PACKED   : usize = (N * QBITS) / 8;
+pub const POLT1_SIZE_PACKED : usize = (N * (QBITS - D)) / 8;
+pub const POLT0_SIZE_PACKED : usize = (N * D) / 8;
+pub const POLZ_SIZE_PACKED  : usize = (N * (QBITS - 3)) / 8;
+pub const POLW1_SIZE_PACKED : usize = (N * 4) / 8;
+
+/// Maximum length of a context string, shared by `sign` and `mldsa`.
+pub const CONTEXT_MAX_BYTES: usize = 255;
+
+/// The context string is longer than `CONTEXT_MAX_BYTES`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ContextTooLong;
+
+impl fmt::Display for ContextTooLong {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("context string longer than 255 bytes")
+    }
 }
 
-#[cfg(feature = "mode1")]
-mod mode {
-    pub const K       : usize = 4;
-    pub const L       : usize = 3;
-    pub const ETA     : u32   = 6;
-    pub const SETABITS: usize = 4;
-    pub const BETA    : u32   = 325;
-    pub const OMEGA   : usize = 80;
+#[cfg(feature = "std")]
+impl ::std::error::Error for ContextTooLong {}
+
+
+/// A Dilithium parameter set.
+///
+/// Every security level is a zero-sized type implementing this trait,
+/// so that several modes can be used side by side in the same program.
+pub trait Mode {
+    const K       : usize;
+    const L       : usize;
+    const ETA     : u32;
+    const SETABITS: usize;
+    const BETA    : u32;
+    const OMEGA   : usize;
+
+    const POLETA_SIZE_PACKED : usize = (N * Self::SETABITS) / 8;
+    const POLVECK_SIZE_PACKED: usize = Self::K * POL_SIZE_PACKED;
+    const POLVECL_SIZE_PACKED: usize = Self::L * POL_SIZE_PACKED;
+    const PK_SIZE_PACKED     : usize = SEEDBYTES + Self::K * POLT1_SIZE_PACKED;
+    const SK_SIZE_PACKED     : usize = 2 * SEEDBYTES + (Self::L + Self::K) * Self::POLETA_SIZE_PACKED + CRHBYTES + Self::K * POLT0_SIZE_PACKED;
+    const SIG_SIZE_PACKED    : usize = Self::L * POLZ_SIZE_PACKED + (Self::OMEGA + Self::K) + (N / 8 + 8);
+
+    const PUBLICKEYBYTES: usize = Self::PK_SIZE_PACKED;
+    const SECRETKEYBYTES: usize = Self::SK_SIZE_PACKED;
+    const BYTES         : usize = Self::SIG_SIZE_PACKED;
+
+    type PolyVecL: PolyVec;
+    type PolyVecK: PolyVec;
+    type Matrix: Copy + Default + Send + Sync + Index<usize, Output = Self::PolyVecL> + IndexMut<usize>;
+
+    type PublicKey: Bytes;
+    type SecretKey: Bytes;
+    type Signature: Bytes;
 }
 
-#[cfg(feature = "mode2")]
-mod mode {
-    pub const K       : usize = 5;
-    pub const L       : usize = 4;
-    pub const ETA     : u32   = 5;
-    pub const SETABITS: usize = 4;
-    pub const BETA    : u32   = 275;
-    pub const OMEGA   : usize = 96;
+/// Fixed size byte array used for packed keys and signatures.
+pub trait Bytes: Copy + Send + Sync + AsRef<[u8]> + AsMut<[u8]> + Zeroize {
+    fn zero() -> Self;
 }
 
-#[cfg(feature = "mode3")]
-mod mode {
-    pub const K       : usize = 6;
-    pub const L       : usize = 5;
-    pub const ETA     : u32   = 3;
-    pub const SETABITS: usize = 3;
-    pub const BETA    : u32   = 175;
-    pub const OMEGA   : usize = 120;
+impl<const LEN: usize> Bytes for [u8; LEN] {
+    #[inline]
+    fn zero() -> Self {
+        [0; LEN]
+    }
 }
 
-pub use self::mode::*;
+macro_rules! mode {
+    (
+        $( #[$attr:meta] )*
+        $mode:ident {
+            K: $k:expr, L: $l:expr,
+            ETA: $eta:expr, SETABITS: $setabits:expr,
+            BETA: $beta:expr, OMEGA: $omega:expr
+        }
+    ) => {
+        $( #[$attr] )*
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct $mode;
 
+        impl Mode for $mode {
+            const K       : usize = $k;
+            const L       : usize = $l;
+            const ETA     : u32   = $eta;
+            const SETABITS: usize = $setabits;
+            const BETA    : u32   = $beta;
+            const OMEGA   : usize = $omega;
 
-pub const POL_SIZE_PACKED   : usize = (N * QBITS) / 8;
-pub const POLT1_SIZE_PACKED : usize = (N * (QBITS - D)) / 8;
-pub const POLT0_SIZE_PACKED : usize = (N * D) / 8;
-pub const POLETA_SIZE_PACKED: usize = (N * SETABITS) / 8;
-pub const POLZ_SIZE_PACKED  : usize = (N * (QBITS - 3)) / 8;
-pub const POLW1_SIZE_PACKED : usize = (N * 4) / 8;
+            type PolyVecL = PolyArray<$l>;
+            type PolyVecK = PolyArray<$k>;
+            type Matrix = [PolyArray<$l>; $k];
+
+            type PublicKey = [u8; <$mode as Mode>::PK_SIZE_PACKED];
+            type SecretKey = [u8; <$mode as Mode>::SK_SIZE_PACKED];
+            type Signature = [u8; <$mode as Mode>::SIG_SIZE_PACKED];
+        }
+    }
+}
+
+mode!(
+    /// Weak parameters.
+    Mode0 { K: 3, L: 2, ETA: 7, SETABITS: 4, BETA: 375, OMEGA: 64 }
+);
+
+mode!(
+    /// Medium parameters.
+    Mode1 { K: 4, L: 3, ETA: 6, SETABITS: 4, BETA: 325, OMEGA: 80 }
+);
 
-pub const POLVECK_SIZE_PACKED: usize = K * POL_SIZE_PACKED;
-pub const POLVECL_SIZE_PACKED: usize = L * POL_SIZE_PACKED;
-pub const PK_SIZE_PACKED     : usize = SEEDBYTES + K * POLT1_SIZE_PACKED;
-pub const SK_SIZE_PACKED     : usize = 2 * SEEDBYTES + (L + K) * POLETA_SIZE_PACKED + CRHBYTES + K * POLT0_SIZE_PACKED;
-pub const SIG_SIZE_PACKED    : usize = L * POLZ_SIZE_PACKED + (OMEGA + K) + (N / 8 + 8);
+mode!(
+    /// Recommended parameters.
+    Mode2 { K: 5, L: 4, ETA: 5, SETABITS: 4, BETA: 275, OMEGA: 96 }
+);
 
-pub const PUBLICKEYBYTES: usize = PK_SIZE_PACKED;
-pub const SECRETKEYBYTES: usize = SK_SIZE_PACKED;
-pub const BYTES         : usize = SIG_SIZE_PACKED;
+mode!(
+    /// Very high parameters.
+    Mode3 { K: 6, L: 5, ETA: 3, SETABITS: 3, BETA: 175, OMEGA: 120 }
+);
 
 pub const MONT: u64   = 4193792;
 pub const QINV: usize = 4236238847;
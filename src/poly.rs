@@ -1,8 +1,9 @@
 use byteorder::{ ByteOrder, LittleEndian };
+use subtle::{ Choice, ConstantTimeGreater };
 use ::params::{
-    Q, N, D, ETA, GAMMA1,
+    Mode, Q, N, D, GAMMA1,
     SEEDBYTES, CRHBYTES,
-    POLT1_SIZE_PACKED, POLETA_SIZE_PACKED, POLZ_SIZE_PACKED, POLW1_SIZE_PACKED
+    POLT1_SIZE_PACKED, POLZ_SIZE_PACKED, POLW1_SIZE_PACKED
 };
 use ::reduce::{ reduce32, montgomery_reduce, freeze as xfreeze, csubq as xcsubq };
 use ::rounding;
@@ -48,6 +49,13 @@ pub fn sub(c: &mut Poly, a: &Poly, b: &Poly) {
     }
 }
 
+#[cfg(any(feature = "low-memory", test))]
+pub fn sub_assign(c: &mut Poly, a: &Poly) {
+    for i in 0..N {
+        c[i] = c[i] + 2 * Q - a[i];
+    }
+}
+
 pub fn shift_left(a: &mut Poly, k: u32) {
     for i in 0..N {
         a[i] <<= k;
@@ -60,6 +68,20 @@ pub fn pointwise_invmontgomery(c: &mut Poly, a: &Poly, b: &Poly) {
     }
 }
 
+#[cfg(any(feature = "low-memory", test))]
+pub fn pointwise_invmontgomery_assign(c: &mut Poly, a: &Poly) {
+    for i in 0..N {
+        c[i] = montgomery_reduce(u64::from(c[i]) * u64::from(a[i]));
+    }
+}
+
+#[cfg(any(feature = "low-memory", test))]
+pub fn pointwise_acc_invmontgomery(c: &mut Poly, a: &Poly, b: &Poly) {
+    for i in 0..N {
+        c[i] += montgomery_reduce(u64::from(a[i]) * u64::from(b[i]));
+    }
+}
+
 pub fn power2round(a: &Poly, a0: &mut Poly, a1: &mut Poly) {
     for i in 0..N {
         let (x, y) = rounding::power2round(a[i]);
@@ -100,14 +122,17 @@ pub fn chknorm(a: &Poly, b: u32) -> bool {
             t ^= t >> 31;
             ((Q - 1) / 2) as i32 - t
         })
-        .any(|t| t as u32 >= b)
+        .fold(Choice::from(0), |acc, t| acc | !b.ct_gt(&(t as u32)))
+        .into()
 }
 
-pub fn uniform(a: &mut Poly, buf: &[u8]) {
+/// Fill `a` with uniform coefficients by rejection sampling on `buf`,
+/// returning how many were written.
+pub fn rej_uniform(a: &mut [u32], buf: &[u8]) -> usize {
     let mut ctr = 0;
     let mut pos = 0;
 
-    while ctr < N {
+    while ctr < a.len() && pos + 3 <= buf.len() {
         let val = LittleEndian::read_u24(&buf[pos..]) & 0x7f_ffff;
         pos += 3;
 
@@ -116,31 +141,33 @@ pub fn uniform(a: &mut Poly, buf: &[u8]) {
             ctr += 1;
         }
     }
+
+    ctr
 }
 
-pub fn uniform_eta(a: &mut Poly, seed: &[u8; SEEDBYTES], nonce: u8) {
+pub fn uniform_eta<P: Mode>(a: &mut Poly, seed: &[u8; SEEDBYTES], nonce: u8) {
     use digest::{ Input, ExtendableOutput, XofReader };
     use sha3::Shake256;
 
     const SHAKE256_RATE: usize = 136;
 
-    fn rej_eta(a: &mut [u32], buf: &[u8]) -> usize {
+    fn rej_eta<P: Mode>(a: &mut [u32], buf: &[u8]) -> usize {
         let mut ctr = 0;
         let mut pos = 0;
         let len = a.len();
 
         while ctr < len && pos < buf.len() {
             let (t0, t1) =
-                if ETA <= 3 { (u32::from(buf[pos] & 0x07), u32::from(buf[pos] >> 5)) }
+                if P::ETA <= 3 { (u32::from(buf[pos] & 0x07), u32::from(buf[pos] >> 5)) }
                 else { (u32::from(buf[pos] & 0x0f), u32::from(buf[pos] >> 4)) };
             pos += 1;
 
-            if t0 <= 2 * ETA {
-                a[ctr] = Q + ETA - t0;
+            if t0 <= 2 * P::ETA {
+                a[ctr] = Q + P::ETA - t0;
                 ctr += 1;
             }
-            if t1 <= 2 * ETA && ctr < len {
-                a[ctr] = Q + ETA - t1;
+            if t1 <= 2 * P::ETA && ctr < len {
+                a[ctr] = Q + P::ETA - t1;
                 ctr += 1;
             }
 
@@ -160,10 +187,10 @@ pub fn uniform_eta(a: &mut Poly, seed: &[u8; SEEDBYTES], nonce: u8) {
     let mut xof = hasher.xof_result();
     xof.read(&mut outbuf);
 
-    let ctr = rej_eta(a, &outbuf);
+    let ctr = rej_eta::<P>(a, &outbuf);
     if ctr < N {
         xof.read(&mut outbuf[..SHAKE256_RATE]);
-        rej_eta(&mut a[ctr..], &outbuf[..SHAKE256_RATE]);
+        rej_eta::<P>(&mut a[ctr..], &outbuf[..SHAKE256_RATE]);
     }
 }
 
@@ -206,7 +233,11 @@ pub fn uniform_gamma1m1(a: &mut Poly, seed: &[u8; SEEDBYTES], mu: &[u8; CRHBYTES
         ctr
     }
 
-    let mut outbuf = [0; 5 * SHAKE256_RATE];
+    // Five blocks, then one more whose last byte is dropped, in groups of 5 bytes
+    const GROUPS: usize = (6 * SHAKE256_RATE - 1) / 5;
+    const CHUNK: usize = SHAKE256_RATE / 5;
+
+    let mut outbuf = [0; 5 * CHUNK];
     let mut nonce_bytes = [0; 2];
     LittleEndian::write_u16(&mut nonce_bytes, nonce);
 
@@ -216,28 +247,28 @@ pub fn uniform_gamma1m1(a: &mut Poly, seed: &[u8; SEEDBYTES], mu: &[u8; CRHBYTES
     hasher.process(&nonce_bytes);
 
     let mut xof = hasher.xof_result();
-    xof.read(&mut outbuf);
-
-    let ctr = rej_gemma1m1(a, &outbuf);
-    if ctr < N {
-        xof.read(&mut outbuf[..SHAKE256_RATE]);
-        rej_gemma1m1(&mut a[ctr..], &outbuf[..SHAKE256_RATE]);
+    let (mut ctr, mut groups) = (0, 0);
+    while ctr < N && groups < GROUPS {
+        let n = CHUNK.min(GROUPS - groups);
+        xof.read(&mut outbuf[..5 * n]);
+        ctr += rej_gemma1m1(&mut a[ctr..], &outbuf[..5 * n]);
+        groups += n;
     }
 }
 
 #[inline]
-pub fn eta_pack(r: &mut [u8; POLETA_SIZE_PACKED], a: &Poly) {
-    if ETA <= 3 {
+pub fn eta_pack<P: Mode>(r: &mut [u8], a: &Poly) {
+    if P::ETA <= 3 {
         let mut t = [0; 8];
         for i in 0..(N / 8) {
-            t[0] = (Q + ETA - a[8*i+0]) as u8;
-            t[1] = (Q + ETA - a[8*i+1]) as u8;
-            t[2] = (Q + ETA - a[8*i+2]) as u8;
-            t[3] = (Q + ETA - a[8*i+3]) as u8;
-            t[4] = (Q + ETA - a[8*i+4]) as u8;
-            t[5] = (Q + ETA - a[8*i+5]) as u8;
-            t[6] = (Q + ETA - a[8*i+6]) as u8;
-            t[7] = (Q + ETA - a[8*i+7]) as u8;
+            t[0] = (Q + P::ETA - a[8*i+0]) as u8;
+            t[1] = (Q + P::ETA - a[8*i+1]) as u8;
+            t[2] = (Q + P::ETA - a[8*i+2]) as u8;
+            t[3] = (Q + P::ETA - a[8*i+3]) as u8;
+            t[4] = (Q + P::ETA - a[8*i+4]) as u8;
+            t[5] = (Q + P::ETA - a[8*i+5]) as u8;
+            t[6] = (Q + P::ETA - a[8*i+6]) as u8;
+            t[7] = (Q + P::ETA - a[8*i+7]) as u8;
 
             r[3*i+0]  = t[0];
             r[3*i+0] |= t[1] << 3;
@@ -253,16 +284,16 @@ pub fn eta_pack(r: &mut [u8; POLETA_SIZE_PACKED], a: &Poly) {
     } else {
         let mut t = [0; 2];
         for i in 0..(N / 2) {
-            t[0] = (Q + ETA - a[2*i+0]) as u8;
-            t[1] = (Q + ETA - a[2*i+1]) as u8;
+            t[0] = (Q + P::ETA - a[2*i+0]) as u8;
+            t[1] = (Q + P::ETA - a[2*i+1]) as u8;
             r[i] = t[0] | (t[1] << 4);
         }
     }
 }
 
 #[inline]
-pub fn eta_unpack(r: &mut Poly, a: &[u8; POLETA_SIZE_PACKED]) {
-    if ETA <= 3 {
+pub fn eta_unpack<P: Mode>(r: &mut Poly, a: &[u8]) {
+    if P::ETA <= 3 {
         for i in 0..(N / 8) {
             r[8*i+0] = u32::from(a[3*i+0]) & 0x07;
             r[8*i+1] = (u32::from(a[3*i+0]) >> 3) & 0x07;
@@ -273,25 +304,35 @@ pub fn eta_unpack(r: &mut Poly, a: &[u8; POLETA_SIZE_PACKED]) {
             r[8*i+6] = (u32::from(a[3*i+2]) >> 2) & 0x07;
             r[8*i+7] = u32::from(a[3*i+2]) >> 5;
 
-            r[8*i+0] = Q + ETA - r[8*i+0];
-            r[8*i+1] = Q + ETA - r[8*i+1];
-            r[8*i+2] = Q + ETA - r[8*i+2];
-            r[8*i+3] = Q + ETA - r[8*i+3];
-            r[8*i+4] = Q + ETA - r[8*i+4];
-            r[8*i+5] = Q + ETA - r[8*i+5];
-            r[8*i+6] = Q + ETA - r[8*i+6];
-            r[8*i+7] = Q + ETA - r[8*i+7];
+            r[8*i+0] = Q + P::ETA - r[8*i+0];
+            r[8*i+1] = Q + P::ETA - r[8*i+1];
+            r[8*i+2] = Q + P::ETA - r[8*i+2];
+            r[8*i+3] = Q + P::ETA - r[8*i+3];
+            r[8*i+4] = Q + P::ETA - r[8*i+4];
+            r[8*i+5] = Q + P::ETA - r[8*i+5];
+            r[8*i+6] = Q + P::ETA - r[8*i+6];
+            r[8*i+7] = Q + P::ETA - r[8*i+7];
         }
     } else {
         for i in 0..(N / 2) {
             r[2*i+0] = u32::from(a[i]) & 0x0F;
             r[2*i+1] = u32::from(a[i]) >> 4;
-            r[2*i+0] = Q + ETA - r[2*i+0];
-            r[2*i+1] = Q + ETA - r[2*i+1];
+            r[2*i+0] = Q + P::ETA - r[2*i+0];
+            r[2*i+1] = Q + P::ETA - r[2*i+1];
         }
     }
 }
 
+/// `eta_unpack` that also checks every coefficient lies in `[-ETA, ETA]`.
+pub fn eta_unpack_checked<P: Mode>(r: &mut Poly, a: &[u8]) -> bool {
+    eta_unpack::<P>(r, a);
+
+    // The packed values are `ETA - r`, valid ones are at most `2 ETA`
+    let invalid = r.iter()
+        .fold(Choice::from(0), |acc, &x| acc | (Q + P::ETA - x).ct_gt(&(2 * P::ETA)));
+    !bool::from(invalid)
+}
+
 #[inline]
 pub fn t0_pack(r: &mut [u8], a: &Poly) {
     let mut t = [0; 4];
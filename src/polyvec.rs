@@ -1,144 +1,164 @@
 #![allow(dead_code)]
 
-use ::params::{ N, L, K };
+use core::ops::{ Index, IndexMut };
+use subtle::{ Choice, ConstantTimeEq };
+use zeroize::Zeroize;
+use ::params::N;
 use ::poly::{ self, Poly };
 
 
-macro_rules! polyvec {
-    ( $polyvec:ident, $len:expr ) => {
-        #[derive(Copy, Clone)]
-        pub struct $polyvec(pub [Poly; $len]);
-
-        impl $polyvec {
-            pub fn reduce(&mut self) {
-                self.0.iter_mut()
-                    .for_each(poly::reduce)
-            }
-
-            pub fn csubq(&mut self) {
-                self.0.iter_mut()
-                    .for_each(poly::csubq)
-            }
-
-            pub fn freeze(&mut self) {
-                self.0.iter_mut()
-                    .for_each(poly::freeze)
-            }
-
-            pub fn with_add(&mut self, u: &Self, v: &Self) {
-                for i in 0..$len {
-                    poly::add(&mut self[i], &u[i], &v[i]);
-                }
-            }
-
-            pub fn add_assign(&mut self, u: &Self) {
-                for i in 0..$len {
-                    poly::add_assign(&mut self[i], &u[i]);
-                }
-            }
-
-            pub fn with_sub(&mut self, u: &Self, v: &Self) {
-                for i in 0..$len {
-                    poly::sub(&mut self[i], &u[i], &v[i]);
-                }
-            }
-
-            pub fn shift_left(&mut self, k: u32) {
-                self.0.iter_mut()
-                    .for_each(|p| poly::shift_left(p, k));
-            }
-
-            pub fn ntt(&mut self) {
-                self.0.iter_mut()
-                    .for_each(poly::ntt);
-            }
-
-            pub fn invntt_montgomery(&mut self) {
-                self.0.iter_mut()
-                    .for_each(poly::invntt_montgomery)
-            }
-
-            pub fn chknorm(&self, bound: u32) -> bool {
-                self.0.iter()
-                    .map(|p| poly::chknorm(p, bound))
-                    .fold(false, |x, y| x | y)
-            }
-        }
+/// Vector of polynomials, either of length `L` or `K`.
+pub trait PolyVec: Copy + Default + Eq + Send + Sync + Zeroize + Index<usize, Output = Poly> + IndexMut<usize> {
+    const LEN: usize;
+
+    fn as_slice(&self) -> &[Poly];
+    fn as_mut_slice(&mut self) -> &mut [Poly];
+
+    fn reduce(&mut self) {
+        self.as_mut_slice().iter_mut()
+            .for_each(poly::reduce)
+    }
 
-        impl ::core::ops::Index<usize> for $polyvec {
-            type Output = Poly;
+    fn csubq(&mut self) {
+        self.as_mut_slice().iter_mut()
+            .for_each(poly::csubq)
+    }
+
+    fn freeze(&mut self) {
+        self.as_mut_slice().iter_mut()
+            .for_each(poly::freeze)
+    }
 
-            #[inline(always)]
-            fn index(&self, i: usize) -> &Self::Output {
-                self.0.index(i)
-            }
+    fn with_add(&mut self, u: &Self, v: &Self) {
+        for i in 0..Self::LEN {
+            poly::add(&mut self[i], &u[i], &v[i]);
         }
+    }
 
-        impl ::core::ops::IndexMut<usize> for $polyvec {
-            #[inline(always)]
-            fn index_mut(&mut self, i: usize) -> &mut Self::Output {
-                self.0.index_mut(i)
-            }
+    fn add_assign(&mut self, u: &Self) {
+        for i in 0..Self::LEN {
+            poly::add_assign(&mut self[i], &u[i]);
         }
+    }
 
-        impl ::core::cmp::PartialEq for $polyvec {
-            fn eq(&self, other: &Self) -> bool {
-                self.0.iter().zip(&other.0)
-                    .flat_map(|(x, y)| x.iter().zip(y.iter()))
-                    .all(|(x, y)| x == y)
-            }
+    fn with_sub(&mut self, u: &Self, v: &Self) {
+        for i in 0..Self::LEN {
+            poly::sub(&mut self[i], &u[i], &v[i]);
         }
+    }
 
-        impl Eq for $polyvec {}
+    fn shift_left(&mut self, k: u32) {
+        self.as_mut_slice().iter_mut()
+            .for_each(|p| poly::shift_left(p, k));
+    }
 
-        impl Default for $polyvec {
-            fn default() -> Self {
-                $polyvec([[0; N]; $len])
-            }
+    fn ntt(&mut self) {
+        self.as_mut_slice().iter_mut()
+            .for_each(poly::ntt);
+    }
+
+    fn invntt_montgomery(&mut self) {
+        self.as_mut_slice().iter_mut()
+            .for_each(poly::invntt_montgomery)
+    }
+
+    fn chknorm(&self, bound: u32) -> bool {
+        self.as_slice().iter()
+            .map(|p| poly::chknorm(p, bound))
+            .fold(false, |x, y| x | y)
+    }
+
+    fn power2round(&self, v0: &mut Self, v1: &mut Self) {
+        for i in 0..Self::LEN {
+            poly::power2round(&self[i], &mut v0[i], &mut v1[i]);
+        }
+    }
+
+    fn decompose(&self, v0: &mut Self, v1: &mut Self) {
+        for i in 0..Self::LEN {
+            poly::decompose(&self[i], &mut v0[i], &mut v1[i]);
         }
     }
 }
 
-polyvec!(PolyVecL, L);
-polyvec!(PolyVecK, K);
+#[derive(Copy, Clone)]
+pub struct PolyArray<const LEN: usize>(pub [Poly; LEN]);
 
-pub fn pointwise_acc_invmontgomery(w: &mut Poly, u: &PolyVecL, v: &PolyVecL) {
-    let mut t = [0; N];
+impl<const LEN: usize> PolyVec for PolyArray<LEN> {
+    const LEN: usize = LEN;
 
-    poly::pointwise_invmontgomery(w, &u[0], &v[0]);
+    #[inline(always)]
+    fn as_slice(&self) -> &[Poly] {
+        &self.0
+    }
 
-    for i in 1..L {
-        poly::pointwise_invmontgomery(&mut t, &u[i], &v[i]);
-        poly::add_assign(w, &t);
+    #[inline(always)]
+    fn as_mut_slice(&mut self) -> &mut [Poly] {
+        &mut self.0
     }
+}
 
-    poly::reduce(w);
+impl<const LEN: usize> Index<usize> for PolyArray<LEN> {
+    type Output = Poly;
+
+    #[inline(always)]
+    fn index(&self, i: usize) -> &Self::Output {
+        self.0.index(i)
+    }
 }
 
-impl PolyVecK {
-    pub fn power2round(&self, v0: &mut Self, v1: &mut Self) {
-        for i in 0..K {
-            poly::power2round(&self[i], &mut v0[i], &mut v1[i]);
-        }
+impl<const LEN: usize> IndexMut<usize> for PolyArray<LEN> {
+    #[inline(always)]
+    fn index_mut(&mut self, i: usize) -> &mut Self::Output {
+        self.0.index_mut(i)
+    }
+}
+
+impl<const LEN: usize> PartialEq for PolyArray<LEN> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0)
+            .fold(Choice::from(1), |acc, (x, y)| acc & x[..].ct_eq(&y[..]))
+            .into()
     }
+}
 
-    pub fn decompose(&self, v0: &mut Self, v1: &mut Self) {
-        for i in 0..K {
-            poly::decompose(&self[i], &mut v0[i], &mut v1[i]);
-        }
+impl<const LEN: usize> Eq for PolyArray<LEN> {}
+
+impl<const LEN: usize> Zeroize for PolyArray<LEN> {
+    fn zeroize(&mut self) {
+        self.0.iter_mut().for_each(Zeroize::zeroize);
     }
 }
 
-pub fn make_hint(h: &mut PolyVecK, u: &PolyVecK, v: &PolyVecK) -> usize {
+impl<const LEN: usize> Default for PolyArray<LEN> {
+    fn default() -> Self {
+        PolyArray([[0; N]; LEN])
+    }
+}
+
+pub fn pointwise_acc_invmontgomery<V: PolyVec>(w: &mut Poly, u: &V, v: &V) {
+    let mut t = [0; N];
+
+    poly::pointwise_invmontgomery(w, &u[0], &v[0]);
+
+    for i in 1..V::LEN {
+        poly::pointwise_invmontgomery(&mut t, &u[i], &v[i]);
+        poly::add_assign(w, &t);
+    }
+
+    poly::reduce(w);
+}
+
+pub fn make_hint<V: PolyVec>(h: &mut V, u: &V, v: &V) -> usize {
     let mut s = 0;
-    for i in 0..K {
+    for i in 0..V::LEN {
         s += poly::make_hint(&u[i], &v[i], &mut h[i]);
     }
     s
 }
 
-pub fn use_hint(w: &mut PolyVecK, u: &PolyVecK, h: &PolyVecK) {
-    for i in 0..K {
+pub fn use_hint<V: PolyVec>(w: &mut V, u: &V, h: &V) {
+    for i in 0..V::LEN {
         poly::use_hint(&mut w[i], &u[i], &h[i]);
     }
 }
@@ -1,4 +1,6 @@
+use subtle::ConditionallySelectable;
 use ::params::{ Q, QINV };
+use ::utils::ct_is_negative;
 
 
 pub fn montgomery_reduce(a: u64) -> u32 {
@@ -16,14 +18,12 @@ pub fn reduce32(mut a: u32) -> u32 {
     t
 }
 
-pub fn csubq(mut a: u32) -> u32 {
-    a = a.wrapping_sub(Q as u32);
-    let c = ((a as i32) >> 31) & Q as i32;
-    a.wrapping_add(c as u32)
+pub fn csubq(a: u32) -> u32 {
+    let b = a.wrapping_sub(Q);
+    u32::conditional_select(&b, &a, ct_is_negative(b as i32))
 }
 
 pub fn freeze(a: u32) -> u32 {
     let a = reduce32(a);
-    let a = csubq(a);
-    a
+    csubq(a)
 }
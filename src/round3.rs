@@ -0,0 +1,134 @@
+//! Dilithium round 3 (CRYSTALS-Dilithium v3.1) compatible mode.
+//!
+//! Key and signature layouts match the round 3 submission, so this can
+//! verify signatures made by peers that have not yet moved to ML-DSA.
+//! It shares everything with `mldsa` but the seed expansion, the 32 byte
+//! `tr`, and the absence of a context string in `mu`.
+//!
+//! Signing panics on a secret key with `s1` or `s2` coefficients outside
+//! `[-ETA, ETA]`.
+
+use rand_core::{ RngCore, CryptoRng };
+use zeroize::Zeroize;
+use ::params::Q;
+use ::mldsa::{ self, Params };
+use ::mldsa::params::{ SEEDBYTES, CRHBYTES };
+use ::sign::{ VerifyError, attach, detach, expect_valid_key };
+
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Marker for the round 3 parameter sets, sealed like `mldsa::MlDsa`.
+pub trait Round3: Params + private::Sealed {}
+
+params!(
+    /// Dilithium2, NIST security level 2.
+    Dilithium2 {
+        K: 4, L: 4, ETA: 2, TAU: 39, BETA: 78,
+        GAMMA1: 1 << 17, GAMMA2: (Q - 1) / 88, OMEGA: 80,
+        CTILDEBYTES: 32, TRBYTES: 32
+    }
+);
+
+params!(
+    /// Dilithium3, NIST security level 3.
+    Dilithium3 {
+        K: 6, L: 5, ETA: 4, TAU: 49, BETA: 196,
+        GAMMA1: 1 << 19, GAMMA2: (Q - 1) / 32, OMEGA: 55,
+        CTILDEBYTES: 32, TRBYTES: 32
+    }
+);
+
+params!(
+    /// Dilithium5, NIST security level 5.
+    Dilithium5 {
+        K: 8, L: 7, ETA: 2, TAU: 60, BETA: 120,
+        GAMMA1: 1 << 19, GAMMA2: (Q - 1) / 32, OMEGA: 75,
+        CTILDEBYTES: 32, TRBYTES: 32
+    }
+);
+
+impl private::Sealed for Dilithium2 {}
+impl private::Sealed for Dilithium3 {}
+impl private::Sealed for Dilithium5 {}
+
+impl Round3 for Dilithium2 {}
+impl Round3 for Dilithium3 {}
+impl Round3 for Dilithium5 {}
+
+
+pub fn keypair_from_seed<P: Round3>(seed: &[u8; SEEDBYTES], pk: &mut P::PublicKey, sk: &mut P::SecretKey) {
+    let mut seedbuf = [0; 2 * SEEDBYTES + CRHBYTES];
+
+    // Expand 32 bytes of randomness into rho, rhoprime and key
+    shake256!(&mut seedbuf; seed);
+    let rho = array_ref!(seedbuf, 0, SEEDBYTES);
+    let rhoprime = array_ref!(seedbuf, SEEDBYTES, CRHBYTES);
+    let key = array_ref!(seedbuf, SEEDBYTES + CRHBYTES, SEEDBYTES);
+
+    mldsa::keypair_from_seeds::<P>(pk, sk, rho, rhoprime, key);
+    seedbuf.zeroize();
+}
+
+pub fn keypair<P: Round3, R: RngCore + CryptoRng>(rng: &mut R, pk: &mut P::PublicKey, sk: &mut P::SecretKey) {
+    let mut seed = [0; SEEDBYTES];
+    rng.fill_bytes(&mut seed);
+    keypair_from_seed::<P>(&seed, pk, sk);
+    seed.zeroize();
+}
+
+pub fn sign<P: Round3>(sig: &mut P::Signature, m: &[u8], sk: &P::SecretKey) {
+    let (mut mu, mut rhoprime) = ([0; CRHBYTES], [0; CRHBYTES]);
+    let sk_bytes = sk.as_ref();
+    let key = &sk_bytes[SEEDBYTES..][..SEEDBYTES];
+    let tr = &sk_bytes[2 * SEEDBYTES..][..P::TRBYTES];
+
+    // Compute CRH(tr, msg) and the deterministic mask seed CRH(key, mu)
+    shake256!(&mut mu; tr, m);
+    shake256!(&mut rhoprime; key, &mu);
+
+    let r = mldsa::sign_mu::<P>(sig, &mu, &rhoprime, sk);
+    rhoprime.zeroize();
+    expect_valid_key(r);
+}
+
+/// Randomized signing, with a fresh random mask seed as in the reference
+/// `DILITHIUM_RANDOMIZED_SIGNING` build.
+pub fn sign_randomized<P: Round3, R: RngCore + CryptoRng>(rng: &mut R, sig: &mut P::Signature, m: &[u8], sk: &P::SecretKey) {
+    let (mut mu, mut rhoprime) = ([0; CRHBYTES], [0; CRHBYTES]);
+    let tr = &sk.as_ref()[2 * SEEDBYTES..][..P::TRBYTES];
+
+    shake256!(&mut mu; tr, m);
+    rng.fill_bytes(&mut rhoprime);
+
+    let r = mldsa::sign_mu::<P>(sig, &mu, &rhoprime, sk);
+    rhoprime.zeroize();
+    expect_valid_key(r);
+}
+
+pub fn verify<P: Round3>(m: &[u8], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    let (mut tr, mut mu) = ([0; SEEDBYTES], [0; CRHBYTES]);
+
+    // Compute CRH(H(rho, t1), msg)
+    shake256!(&mut tr; pk.as_ref());
+    shake256!(&mut mu; &tr, m);
+
+    mldsa::verify_mu::<P>(&mu, sig, pk)
+}
+
+/// Attached signing, like `crypto_sign` of the round 3 reference: write `sig || m` to `sm`.
+///
+/// # Panics
+///
+/// If `sm` is not `P::BYTES + m.len()` bytes long.
+pub fn sign_message<P: Round3>(sm: &mut [u8], m: &[u8], sk: &P::SecretKey) {
+    attach::<P::Signature, _>(sm, m, |sig| sign::<P>(sig, m, sk))
+}
+
+/// Open an attached signature `sm = sig || m`, like `crypto_sign_open`,
+/// returning the message once the signature is verified.
+pub fn open<'a, P: Round3>(sm: &'a [u8], pk: &P::PublicKey) -> Result<&'a [u8], VerifyError> {
+    detach::<P::Signature, _>(sm, |m, sig| verify::<P>(m, sig, pk))
+}
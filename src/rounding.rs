@@ -1,4 +1,6 @@
+use subtle::{ ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater };
 use ::params::{ Q, D, ALPHA };
+use ::utils::ct_is_negative;
 
 
 pub fn power2round(a: u32) -> (u32, u32) {
@@ -6,7 +8,7 @@ pub fn power2round(a: u32) -> (u32, u32) {
 
     let mut t = (a & ((1 << d) - 1)) as i32;
     t -= (1 << (d - 1)) + 1;
-    t += (t >> 31) & (1 << d);
+    t = i32::conditional_select(&t, &(t + (1 << d)), ct_is_negative(t));
     t -= (1 << (d - 1)) - 1;
     (Q.wrapping_add(t as u32), a.wrapping_sub(t as u32) >> d)
 }
@@ -17,14 +19,13 @@ pub fn decompose(mut a: u32) -> (u32, u32) {
     let mut t = (a & 0x7_ffff) as i32;
     t += ((a >> 19) << 9) as i32;
     t -= alpha / 2 + 1;
-    t += (t >> 31) & alpha;
+    t = i32::conditional_select(&t, &(t + alpha), ct_is_negative(t));
     t -= alpha / 2 - 1;
     a = a.wrapping_sub(t as u32);
 
-    let mut u = (a as i32) - 1;
-    u >>= 31;
+    let u = ct_is_negative((a as i32) - 1);
     a = (a >> 19) + 1;
-    a -= (u & 1) as u32;
+    a = u32::conditional_select(&a, &(a - 1), u);
 
     (Q.wrapping_add(t as u32).wrapping_sub(a >> 4), a & 0xf)
 }
@@ -32,17 +33,16 @@ pub fn decompose(mut a: u32) -> (u32, u32) {
 pub fn make_hint(a: u32, b: u32) -> u32 {
     let (_, x) = decompose(a);
     let (_, y) = decompose(b);
-    if x != y { 1 } else { 0 }
+    u32::from((!x.ct_eq(&y)).unwrap_u8())
 }
 
 pub fn use_hint(a: u32, hint: u32) -> u32 {
     let (a0, a1) = decompose(a);
 
-    if hint == 0 {
-        a1
-    } else if a0 > Q {
-        a1.wrapping_add(1) & 0xf
-    } else {
-        a1.wrapping_sub(1) & 0xf
-    }
+    let r = u32::conditional_select(
+        &(a1.wrapping_sub(1) & 0xf),
+        &(a1.wrapping_add(1) & 0xf),
+        a0.ct_gt(&Q)
+    );
+    u32::conditional_select(&r, &a1, hint.ct_eq(&0))
 }
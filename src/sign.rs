@@ -1,47 +1,207 @@
+//! The round 1 Dilithium scheme, over the parameter sets of `params`.
+//!
+//! Signing, and expanding a secret key, panics on a secret key with `s1` or
+//! `s2` coefficients outside `[-ETA, ETA]`, see `validate_secret_key`.
+//! `sign_checked`, `sign_randomized_checked` and `ExpandedSecretKey::try_new`
+//! return an error instead.
+
+use core::fmt;
 use rand_core::{ RngCore, CryptoRng };
 use byteorder::{ ByteOrder, LittleEndian };
+use digest::{ Input, ExtendableOutput, XofReader };
+use sha3::{ Shake128, Shake256 };
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
 use ::params::{
-    N, K, L, Q, D, GAMMA1, GAMMA2, BETA, OMEGA,
-    SEEDBYTES, CRHBYTES, POLW1_SIZE_PACKED,
-    PK_SIZE_PACKED, SK_SIZE_PACKED, SIG_SIZE_PACKED
+    Mode, Bytes, N, Q, D, GAMMA1, GAMMA2,
+    SEEDBYTES, CRHBYTES, POLW1_SIZE_PACKED
 };
-use ::polyvec::{ self, PolyVecL, PolyVecK };
+use ::polyvec::{ self, PolyVec };
 use ::poly::{ self, Poly };
 use ::packing;
+pub use ::params::{ ContextTooLong, CONTEXT_MAX_BYTES };
 
 
+/// Reason a signature was rejected.
+///
+/// Every check involved only looks at public data (the message, the
+/// signature and the public key), so telling them apart does not leak
+/// anything about the secret key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The signature is not a valid encoding, e.g. a malformed hint or
+    /// non-zero padding.
+    MalformedSignature,
+    /// The response vector `z` exceeds the norm bound.
+    NormBoundExceeded,
+    /// The recomputed challenge does not match the one in the signature.
+    ChallengeMismatch,
+    /// The context string is longer than 255 bytes, only returned by the
+    /// verifiers taking a context, `verify_ctx` and those of `mldsa`.
+    ContextTooLong,
+    /// The digest length does not match the hash function named by its OID,
+    /// only returned by `mldsa::verify_digest` and `mldsa::verify_prehash`.
+    DigestLengthMismatch,
+    /// The OID is not a single DER encoded object identifier, only returned
+    /// by `mldsa::verify_digest` and `mldsa::verify_prehash`.
+    MalformedOid
+}
 
-pub(crate) fn expand_mat(mat: &mut [PolyVecL; K], rho: &[u8; SEEDBYTES]) {
-    const SHAKE128_RATE: usize = 168;
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VerifyError::MalformedSignature => f.write_str("malformed signature"),
+            VerifyError::NormBoundExceeded => f.write_str("signature norm bound exceeded"),
+            VerifyError::ChallengeMismatch => f.write_str("signature challenge mismatch"),
+            VerifyError::ContextTooLong => f.write_str("context string longer than 255 bytes"),
+            VerifyError::DigestLengthMismatch => f.write_str("digest length does not match its hash function"),
+            VerifyError::MalformedOid => f.write_str("malformed hash function OID")
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for VerifyError {}
 
-    let mut outbuf = [0; 5 * SHAKE128_RATE];
+/// Reason a packed key was rejected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyError {
+    /// A coefficient of `s1` or `s2` is outside `[-ETA, ETA]`.
+    EtaOutOfRange,
+    /// The `tr` embedded in the secret key is not `CRH` of the public key.
+    TrMismatch,
+    /// The `t0` embedded in the secret key does not match `A s1 + s2`.
+    T0Mismatch
+}
 
-    for i in 0..K {
-        for j in 0..L {
-            shake128!(&mut outbuf; rho, &[(i + (j << 4)) as u8]);
-            poly::uniform(&mut mat[i][j], &outbuf);
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            KeyError::EtaOutOfRange => f.write_str("secret key coefficient out of range"),
+            KeyError::TrMismatch => f.write_str("secret key tr does not match the public key"),
+            KeyError::T0Mismatch => f.write_str("secret key t0 does not match s1 and s2")
         }
     }
 }
 
-pub(crate) fn challenge(c: &mut Poly, mu: &[u8; CRHBYTES], w1: &PolyVecK) {
-    use digest::{ Input, ExtendableOutput, XofReader };
-    use sha3::Shake256;
+#[cfg(feature = "std")]
+impl ::std::error::Error for KeyError {}
 
-    const SHAKE256_RATE: usize = 136;
+/// Iterations of the signing rejection loop, and which check ended the
+/// rejected ones.
+///
+/// The checks run in a different order with the `low-memory` feature, so
+/// an attempt failing several of them may be counted under another one.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignStats {
+    /// Attempts made, including the final one.
+    pub attempts: u32,
+    /// `z` exceeded `GAMMA1 - BETA`.
+    pub z_norm: u32,
+    /// The low bits of `w - cs2` exceeded `GAMMA2 - BETA`.
+    pub r0_norm: u32,
+    /// The high bits of `w - cs2` differed from `w1`.
+    pub w1_mismatch: u32,
+    /// `ct0` exceeded `GAMMA2`.
+    pub ct0_norm: u32,
+    /// More than `OMEGA` hints were needed.
+    pub hint_count: u32
+}
 
-    let mut outbuf = [0; SHAKE256_RATE];
-    let mut w1pack = [0; K * POLW1_SIZE_PACKED];
-    for (i, pack) in w1pack.chunks_mut(POLW1_SIZE_PACKED).enumerate() {
-        let pack = array_mut_ref!(pack, 0, POLW1_SIZE_PACKED);
-        poly::w1_pack(pack, &w1[i]);
+/// Most iterations of the rejection loop before its 16 bit mask nonce would
+/// wrap, as each one takes `L` nonces.
+pub(crate) fn max_nonce_attempts<P: Mode>() -> u32 {
+    u32::from(u16::MAX) / P::L as u32
+}
+
+/// The signing loop used up its attempt budget without producing a signature.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AttemptsExceeded(pub SignStats);
+
+impl fmt::Display for AttemptsExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no signature after {} attempts", self.0.attempts)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for AttemptsExceeded {}
+
+/// The hardened signer found an inconsistency, such as one caused by an
+/// injected fault, and withheld the signature.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FaultDetected {
+    /// The secret key failed the checks of `validate_secret_key`.
+    InvalidKey(KeyError),
+    /// The norm bounds did not hold for the finished signature.
+    NormRecheck,
+    /// The signature did not verify under the derived public key.
+    Verify(VerifyError)
+}
+
+impl fmt::Display for FaultDetected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FaultDetected::InvalidKey(err) => write!(f, "fault detected: {}", err),
+            FaultDetected::NormRecheck => f.write_str("fault detected: signature norm recheck failed"),
+            FaultDetected::Verify(err) => write!(f, "fault detected: {}", err)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for FaultDetected {}
+
+
+
+pub(crate) fn expand_mat<P: Mode>(mat: &mut P::Matrix, rho: &[u8; SEEDBYTES]) {
+    for i in 0..P::K {
+        for j in 0..P::L {
+            expand_mat_entry(&mut mat[i][j], rho, i, j);
+        }
     }
+}
+
+/// Generate the single entry `A[i][j]` of the matrix.
+pub(crate) fn expand_mat_entry(a: &mut Poly, rho: &[u8; SEEDBYTES], i: usize, j: usize) {
+    const SHAKE128_RATE: usize = 168;
+
+    let mut outbuf = [0; SHAKE128_RATE];
+
+    let mut hasher = Shake128::default();
+    hasher.process(rho);
+    hasher.process(&[(i + (j << 4)) as u8]);
+    let mut xof = hasher.xof_result();
+
+    // Squeeze one block at a time, the rate is a multiple of 3
+    let mut ctr = 0;
+    while ctr < N {
+        xof.read(&mut outbuf);
+        ctr += poly::rej_uniform(&mut a[ctr..], &outbuf);
+    }
+}
+
+pub(crate) fn challenge<P: Mode>(c: &mut Poly, mu: &[u8; CRHBYTES], w1: &P::PolyVecK) {
+    let mut w1pack = [0; POLW1_SIZE_PACKED];
 
     let mut hasher = Shake256::default();
     hasher.process(mu);
-    hasher.process(&w1pack);
+    for i in 0..P::K {
+        poly::w1_pack(&mut w1pack, &w1[i]);
+        hasher.process(&w1pack);
+    }
+    challenge_from_hasher(c, hasher);
+}
+
+/// Sample the challenge from a hasher that has absorbed `mu` and the packed `w1`.
+pub(crate) fn challenge_from_hasher(c: &mut Poly, hasher: Shake256) {
+    const SHAKE256_RATE: usize = 136;
+
+    let mut outbuf = [0; SHAKE256_RATE];
+
     let mut xof = hasher.xof_result();
     xof.read(&mut outbuf);
+    *c = [0; N];
 
     let signs = LittleEndian::read_u64(&outbuf);
     let mut pos = 8;
@@ -65,196 +225,1000 @@ pub(crate) fn challenge(c: &mut Poly, mu: &[u8; CRHBYTES], w1: &PolyVecK) {
     }
 }
 
-pub fn keypair<R: RngCore + CryptoRng>(rng: &mut R, pk_bytes: &mut [u8; PK_SIZE_PACKED], sk_bytes: &mut [u8; SK_SIZE_PACKED]) {
+pub fn keypair<P: Mode, R: RngCore + CryptoRng>(rng: &mut R, pk_bytes: &mut P::PublicKey, sk_bytes: &mut P::SecretKey) {
+    let mut seed = [0; SEEDBYTES];
+    rng.fill_bytes(&mut seed);
+    keypair_from_seed::<P>(&seed, pk_bytes, sk_bytes);
+    seed.zeroize();
+}
+
+/// Deterministically derive a key pair from a 32 byte seed.
+///
+/// `keypair` is `keypair_from_seed` applied to 32 bytes drawn from the RNG,
+/// so the seed is a complete backup of the key pair.
+pub fn keypair_from_seed<P: Mode>(seed: &[u8; SEEDBYTES], pk_bytes: &mut P::PublicKey, sk_bytes: &mut P::SecretKey) {
     let mut nonce = 0;
     let mut tr = [0; CRHBYTES];
     let mut seedbuf = [0; 3 * SEEDBYTES];
-    let mut mat = [PolyVecL::default(); K];
-    let mut s1 = PolyVecL::default();
+    let mut s1 = P::PolyVecL::default();
     let (mut s2, mut t, mut t0, mut t1) =
-        (PolyVecK::default(), PolyVecK::default(), PolyVecK::default(), PolyVecK::default());
+        (P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default());
 
     // Expand 32 bytes of randomness into rho, rhoprime and key
-    rng.fill_bytes(&mut seedbuf[..SEEDBYTES]);
-    shake256!(&mut seedbuf; &seedbuf[..SEEDBYTES]);
+    shake256!(&mut seedbuf; seed);
     let rho = array_ref!(seedbuf, 0, SEEDBYTES);
     let rhoprime = array_ref!(seedbuf, SEEDBYTES, SEEDBYTES);
     let key = array_ref!(seedbuf, 2 * SEEDBYTES, SEEDBYTES);
 
-    // Expand matrix
-    expand_mat(&mut mat, rho);
-
     // Sample short vectors s1 and s2
-    for i in 0..L {
-        poly::uniform_eta(&mut s1[i], rhoprime, nonce);
+    for i in 0..P::L {
+        poly::uniform_eta::<P>(&mut s1[i], rhoprime, nonce);
         nonce += 1;
     }
-    for i in 0..K {
-        poly::uniform_eta(&mut s2[i], rhoprime, nonce);
+    for i in 0..P::K {
+        poly::uniform_eta::<P>(&mut s2[i], rhoprime, nonce);
         nonce += 1;
     }
 
+    // Extract t1 and write public key
+    compute_t::<P>(&mut t, rho, &s1, &s2);
+    t.power2round(&mut t0, &mut t1);
+    packing::pk::pack::<P>(pk_bytes, rho, &t1);
+
+    // Compute CRH(rho, t1) and write secret key
+    shake256!(&mut tr; pk_bytes.as_ref());
+    packing::sk::pack::<P>(sk_bytes, rho, key, &tr, &s1, &s2, &t0);
+
+    // Wipe secret intermediates
+    seedbuf.zeroize();
+    s1.zeroize();
+    s2.zeroize();
+    t.zeroize();
+    t0.zeroize();
+}
+
+/// Compute `t = A s1 + s2`, frozen to `[0, Q)`.
+fn compute_t<P: Mode>(t: &mut P::PolyVecK, rho: &[u8; SEEDBYTES], s1: &P::PolyVecL, s2: &P::PolyVecK) {
+    let mut mat = P::Matrix::default();
+
+    // Expand matrix
+    expand_mat::<P>(&mut mat, rho);
+
     // Matrix-vector multiplication
-    let mut s1hat = s1.clone();
+    let mut s1hat = *s1;
     s1hat.ntt();
-    for i in 0..K {
+    for i in 0..P::K {
         polyvec::pointwise_acc_invmontgomery(&mut t[i], &mat[i], &s1hat);
         poly::reduce(&mut t[i]);
         poly::invntt_montgomery(&mut t[i])
     }
 
     // Add noise vector s2
-    t.add_assign(&s2);
-
-    // Extract t1 and write public key
+    t.add_assign(s2);
     t.freeze();
-    t.power2round(&mut t0, &mut t1);
-    packing::pk::pack(pk_bytes, rho, &t1);
+    s1hat.zeroize();
+}
 
-    // Compute CRH(rho, t1) and write secret key
-    shake256!(&mut tr; pk_bytes);
-    packing::sk::pack(sk_bytes, rho, key, &tr, &s1, &s2, &t0);
+/// Recover the public key of a secret key, which only embeds `CRH(pk)`.
+///
+/// Checks the ranges of `s1` and `s2`, recomputes `t = A s1 + s2` and splits
+/// it into `t1` and `t0`, then checks the result against the `t0` and `tr`
+/// stored in the secret key, so a corrupted or mismatched secret key is
+/// rejected instead of yielding a wrong public key. `pk` is only written
+/// on success.
+pub fn public_key_from_secret<P: Mode>(sk: &P::SecretKey, pk: &mut P::PublicKey) -> Result<(), KeyError> {
+    let (mut rho, mut key) = ([0; SEEDBYTES], [0; SEEDBYTES]);
+    let (mut tr, mut tr2) = ([0; CRHBYTES], [0; CRHBYTES]);
+    let mut s1 = P::PolyVecL::default();
+    let (mut s2, mut t, mut t0, mut t0_2, mut t1) =
+        (P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default());
+    let mut pk2 = P::PublicKey::zero();
+
+    let valid = packing::sk::unpack_checked::<P>(sk, &mut rho, &mut key, &mut tr, &mut s1, &mut s2, &mut t0);
+
+    // Extract t1 and pack the candidate public key
+    compute_t::<P>(&mut t, &rho, &s1, &s2);
+    t.power2round(&mut t0_2, &mut t1);
+    packing::pk::pack::<P>(&mut pk2, &rho, &t1);
+
+    // Check the rest of the secret key against it
+    shake256!(&mut tr2; pk2.as_ref());
+    let r = if !valid {
+        Err(KeyError::EtaOutOfRange)
+    } else if t0 != t0_2 {
+        Err(KeyError::T0Mismatch)
+    } else if !bool::from(tr.ct_eq(&tr2)) {
+        Err(KeyError::TrMismatch)
+    } else {
+        Ok(())
+    };
+
+    // Only release a public key that passed every check
+    if r.is_ok() {
+        *pk = pk2;
+    }
+
+    // Wipe secret intermediates
+    key.zeroize();
+    s1.zeroize();
+    s2.zeroize();
+    t.zeroize();
+    t0.zeroize();
+    t0_2.zeroize();
+    r
 }
 
-pub fn sign(sig: &mut [u8; SIG_SIZE_PACKED], m: &[u8], sk: &[u8; SK_SIZE_PACKED]) {
-    let mut nonce = 0;
-    let mut mat = [PolyVecL::default(); K];
-    let (mut s1, mut y, mut z) =
-        (PolyVecL::default(), PolyVecL::default(), PolyVecL::default());
-    let (mut s2, mut t0, mut w, mut w1) =
-        (PolyVecK::default(), PolyVecK::default(), PolyVecK::default(), PolyVecK::default());
-    let (mut h, mut wcs2, mut wcs20, mut ct0, mut tmp) =
-        (PolyVecK::default(), PolyVecK::default(), PolyVecK::default(), PolyVecK::default(), PolyVecK::default());
-    let (mut rho, mut key, mut mu) = ([0; SEEDBYTES], [0; SEEDBYTES], [0; CRHBYTES]);
+/// Check that a packed secret key is well formed before using it.
+///
+/// Signing trusts `t0` and `tr`, a mismatch of which gives signatures that
+/// do not verify. This rejects such keys, and those signing would panic on,
+/// with an error instead, at the cost of one key generation.
+pub fn validate_secret_key<P: Mode>(sk: &P::SecretKey) -> Result<(), KeyError> {
+    public_key_from_secret::<P>(sk, &mut P::PublicKey::zero())
+}
+
+pub fn sign<P: Mode>(sig: &mut P::Signature, m: &[u8], sk: &P::SecretKey) {
+    expect_valid_key(sign_internal::<P>(sig, m, sk, None))
+}
+
+/// `sign`, returning an error instead of panicking on a secret key with
+/// out of range `s1` or `s2` coefficients.
+pub fn sign_checked<P: Mode>(sig: &mut P::Signature, m: &[u8], sk: &P::SecretKey) -> Result<(), KeyError> {
+    sign_internal::<P>(sig, m, sk, None)
+}
+
+/// Hedged signing, mixing 32 fresh random bytes into the seed of the mask `y`.
+///
+/// The signature verifies exactly like one from `sign`, but repeated
+/// signing of the same message no longer recomputes the same `y`.
+pub fn sign_randomized<P: Mode, R: RngCore + CryptoRng>(rng: &mut R, sig: &mut P::Signature, m: &[u8], sk: &P::SecretKey) {
+    expect_valid_key(sign_randomized_checked::<P, R>(rng, sig, m, sk))
+}
+
+/// `sign_randomized`, returning an error instead of panicking, see `sign_checked`.
+pub fn sign_randomized_checked<P: Mode, R: RngCore + CryptoRng>(
+    rng: &mut R,
+    sig: &mut P::Signature,
+    m: &[u8],
+    sk: &P::SecretKey
+) -> Result<(), KeyError> {
+    let mut rnd = [0; SEEDBYTES];
+    rng.fill_bytes(&mut rnd);
+    let r = sign_internal::<P>(sig, m, sk, Some(&rnd));
+    rnd.zeroize();
+    r
+}
 
-    packing::sk::unpack(sk, &mut rho, &mut key, &mut mu, &mut s1, &mut s2, &mut t0);
+/// Panic on a secret key rejected while signing, for the signing functions
+/// documented to do so.
+pub(crate) fn expect_valid_key(r: Result<(), KeyError>) {
+    if let Err(err) = r {
+        panic!("{}", err);
+    }
+}
+
+fn sign_internal<P: Mode>(sig: &mut P::Signature, m: &[u8], sk: &P::SecretKey, rnd: Option<&[u8; SEEDBYTES]>) -> Result<(), KeyError> {
+    let mut mu = [0; CRHBYTES];
 
     // Compute CRH(tr, msg)
-    shake256!(&mut mu; &mu, m);
+    shake256!(&mut mu; &sk.as_ref()[2 * SEEDBYTES..][..CRHBYTES], m);
+    sign_mu_internal::<P>(sig, &mu, sk, rnd)
+}
+
+/// Signing hardened against fault attacks, at roughly the cost of two key
+/// generations and a verification on top of `sign`: deriving the public key
+/// and rechecking the norms both recompute `A s1 + s2`.
+///
+/// The public key is derived from `sk`, which also validates it, and the
+/// signature is only written to `sig` once the norms of `z`, `r0` and `ct0`
+/// were checked again, recomputed from the signature and `sk`, and it
+/// verified under that key. Otherwise `sig` is left untouched.
+pub fn sign_hardened<P: Mode>(sig: &mut P::Signature, m: &[u8], sk: &P::SecretKey) -> Result<(), FaultDetected> {
+    sign_hardened_internal::<P, _>(sig, m, sk, None, |_| ())
+}
+
+/// Hedged hardened signing, see `sign_hardened` and `sign_randomized`.
+pub fn sign_randomized_hardened<P: Mode, R: RngCore + CryptoRng>(
+    rng: &mut R,
+    sig: &mut P::Signature,
+    m: &[u8],
+    sk: &P::SecretKey
+) -> Result<(), FaultDetected> {
+    let mut rnd = [0; SEEDBYTES];
+    rng.fill_bytes(&mut rnd);
+    let r = sign_hardened_internal::<P, _>(sig, m, sk, Some(&rnd), |_| ());
+    rnd.zeroize();
+    r
+}
 
-    // Expand matrix and transform vectors
-    expand_mat(&mut mat, &rho);
-    s1.ntt();
-    s2.ntt();
+/// `fault` is applied to the signature before it is checked, for tests.
+pub(crate) fn sign_hardened_internal<P: Mode, F: FnOnce(&mut P::Signature)>(
+    sig: &mut P::Signature,
+    m: &[u8],
+    sk: &P::SecretKey,
+    rnd: Option<&[u8; SEEDBYTES]>,
+    fault: F
+) -> Result<(), FaultDetected> {
+    let mut pk = P::PublicKey::zero();
+    let mut sig2 = P::Signature::zero();
+
+    public_key_from_secret::<P>(sk, &mut pk).map_err(FaultDetected::InvalidKey)?;
+    sign_internal::<P>(&mut sig2, m, sk, rnd).map_err(FaultDetected::InvalidKey)?;
+    fault(&mut sig2);
+
+    // Check the norms again and verify, with mu computed from the public key
+    let r = if !recheck_norms::<P>(&sig2, sk) {
+        Err(FaultDetected::NormRecheck)
+    } else {
+        verify::<P>(m, &sig2, &pk).map_err(FaultDetected::Verify)
+    };
+
+    if r.is_ok() {
+        *sig = sig2;
+    }
+    sig2.zeroize();
+    r
+}
+
+/// Whether a packed signature is well formed and passes the norm checks of
+/// the rejection loop again, recomputed from the signature and `sk` alone.
+///
+/// Without `y`, the low bits `r0` of `w - cs2 = A y - c s2` come from
+/// `A z - c t`, with `t = A s1 + s2`. Along with `z` and `ct0` they are
+/// checked against the same bounds as when signing.
+fn recheck_norms<P: Mode>(sig: &P::Signature, sk: &P::SecretKey) -> bool {
+    let mut mat = P::Matrix::default();
+    let (mut rho, mut key, mut tr) = ([0; SEEDBYTES], [0; SEEDBYTES], [0; CRHBYTES]);
+    let (mut z, mut s1) = (P::PolyVecL::default(), P::PolyVecL::default());
+    let (mut h, mut s2, mut t, mut t0) =
+        (P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default());
+    let (mut w, mut r0, mut r1) = (P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default());
+    let mut c = [0; N];
+
+    if !packing::sign::unpack::<P>(sig, &mut z, &mut h, &mut c) {
+        return false;
+    }
+    let mut valid = !z.chknorm(GAMMA1 - P::BETA);
+    valid &= packing::sk::unpack_checked::<P>(sk, &mut rho, &mut key, &mut tr, &mut s1, &mut s2, &mut t0);
+    compute_t::<P>(&mut t, &rho, &s1, &s2);
+
+    // Compute A z
+    expand_mat::<P>(&mut mat, &rho);
+    z.ntt();
+    for i in 0..P::K {
+        polyvec::pointwise_acc_invmontgomery(&mut w[i], &mat[i], &z);
+        poly::invntt_montgomery(&mut w[i]);
+    }
+    w.csubq();
+
+    // Compute c t and c t0
+    let mut chat = c;
+    poly::ntt(&mut chat);
+    t.ntt();
     t0.ntt();
+    for i in 0..P::K {
+        poly::pointwise_invmontgomery(&mut r0[i], &chat, &t[i]);
+        poly::invntt_montgomery(&mut r0[i]);
+        poly::pointwise_invmontgomery(&mut r1[i], &chat, &t0[i]);
+        poly::invntt_montgomery(&mut r1[i]);
+    }
+
+    // Check ct0
+    r1.csubq();
+    valid &= !r1.chknorm(GAMMA2);
+
+    // Check the low bits of w - cs2 = A z - c t
+    t.with_sub(&w, &r0);
+    t.freeze();
+    t.decompose(&mut r0, &mut r1);
+    r0.csubq();
+    valid &= !r0.chknorm(GAMMA2 - P::BETA);
+
+    key.zeroize();
+    s1.zeroize();
+    s2.zeroize();
+    t.zeroize();
+    t0.zeroize();
+    w.zeroize();
+    r0.zeroize();
+    r1.zeroize();
+    valid
+}
+
+/// Sign a message under a context string of at most 255 bytes.
+///
+/// The context is bound into `mu` length-prefixed as `0 || len(ctx) || ctx || m`,
+/// so a signature only verifies with `verify_ctx` under the same context.
+/// A domain tag hashed ahead of `tr` keeps these signatures apart from those
+/// of `sign` over any message. They are not interoperable with ML-DSA.
+pub fn sign_ctx<P: Mode>(sig: &mut P::Signature, m: &[u8], ctx: &[u8], sk: &P::SecretKey) -> Result<(), ContextTooLong> {
+    let mu = compute_mu_ctx(&sk.as_ref()[2 * SEEDBYTES..][..CRHBYTES], ctx, m)?;
+    expect_valid_key(sign_mu_internal::<P>(sig, &mu, sk, None));
+    Ok(())
+}
+
+/// Hedged signing under a context string, see `sign_ctx` and `sign_randomized`.
+pub fn sign_randomized_ctx<P: Mode, R: RngCore + CryptoRng>(
+    rng: &mut R,
+    sig: &mut P::Signature,
+    m: &[u8],
+    ctx: &[u8],
+    sk: &P::SecretKey
+) -> Result<(), ContextTooLong> {
+    let mu = compute_mu_ctx(&sk.as_ref()[2 * SEEDBYTES..][..CRHBYTES], ctx, m)?;
+    sign_mu_randomized::<P, R>(rng, sig, &mu, sk);
+    Ok(())
+}
+
+/// Hashed ahead of `tr` in `compute_mu_ctx`. Plain `mu = CRH(tr || m)` would
+/// need `tr` to start with it.
+const CONTEXT_DOMAIN: &[u8] = b"dilithium-ctx";
+
+/// Compute `mu = CRH(CONTEXT_DOMAIN || tr || 0 || len(ctx) || ctx || m)`.
+fn compute_mu_ctx(tr: &[u8], ctx: &[u8], m: &[u8]) -> Result<[u8; CRHBYTES], ContextTooLong> {
+    if ctx.len() > CONTEXT_MAX_BYTES {
+        return Err(ContextTooLong);
+    }
+
+    let mut mu = [0; CRHBYTES];
+    shake256!(&mut mu; CONTEXT_DOMAIN, tr, &[0, ctx.len() as u8], ctx, m);
+    Ok(mu)
+}
+
+/// Sign a message representative `mu` computed by `compute_mu`.
+///
+/// Gives the same signature as `sign` over the message `mu` was computed
+/// from, so the message itself never has to reach the secret key holder.
+pub fn sign_mu<P: Mode>(sig: &mut P::Signature, mu: &[u8; CRHBYTES], sk: &P::SecretKey) {
+    expect_valid_key(sign_mu_internal::<P>(sig, mu, sk, None))
+}
+
+/// Hedged signing of a message representative `mu`, see `sign_randomized`.
+pub fn sign_mu_randomized<P: Mode, R: RngCore + CryptoRng>(rng: &mut R, sig: &mut P::Signature, mu: &[u8; CRHBYTES], sk: &P::SecretKey) {
+    let mut rnd = [0; SEEDBYTES];
+    rng.fill_bytes(&mut rnd);
+    let r = sign_mu_internal::<P>(sig, mu, sk, Some(&rnd));
+    rnd.zeroize();
+    expect_valid_key(r);
+}
+
+// Kept out of line, inlined into its callers it costs the low-memory
+// signing path about 800 bytes more stack.
+#[inline(never)]
+fn sign_mu_internal<P: Mode>(sig: &mut P::Signature, mu: &[u8; CRHBYTES], sk: &P::SecretKey, rnd: Option<&[u8; SEEDBYTES]>) -> Result<(), KeyError> {
+    let signed = sign_mu_budget::<P>(sig, mu, sk, rnd, u32::MAX, &mut SignStats::default())?;
+    assert!(signed, "signing ran out of mask nonces");
+    Ok(())
+}
+
+/// Run at most `max_attempts` iterations of the rejection loop, returning
+/// whether a signature was written. The budget is capped by `max_nonce_attempts::<P>()`.
+/// Out of range `s1` or `s2` coefficients are rejected before the first attempt.
+fn sign_mu_budget<P: Mode>(
+    sig: &mut P::Signature,
+    mu: &[u8; CRHBYTES],
+    sk: &P::SecretKey,
+    rnd: Option<&[u8; SEEDBYTES]>,
+    max_attempts: u32,
+    stats: &mut SignStats
+) -> Result<bool, KeyError> {
+    #[cfg(feature = "low-memory")]
+    {
+        ::lowmem::sign_mu::<P>(sig, mu, sk, rnd, max_attempts, stats)
+    }
+
+    #[cfg(not(feature = "low-memory"))]
+    {
+        // Filled in place, a by-value `ExpandedSecretKey::new` costs a copy of
+        // the matrix on the stack
+        let mut esk = ExpandedSecretKey::<P>::empty();
+        if !esk.expand(sk) {
+            return Err(KeyError::EtaOutOfRange);
+        }
+        Ok(esk.sign_mu(sig, mu, rnd, max_attempts, stats))
+    }
+}
+
+/// Sign with a budget of `max_attempts` iterations of the rejection loop,
+/// reporting how many were needed and why the others were rejected.
+///
+/// Gives the same signature as `sign` when it succeeds. On failure `sig` is
+/// zeroed, as it may hold parts of rejected attempts. Budgets above
+/// `65535 / L` attempts are capped there, as the mask nonce would wrap.
+pub fn sign_bounded<P: Mode>(sig: &mut P::Signature, m: &[u8], sk: &P::SecretKey, max_attempts: u32) -> Result<SignStats, AttemptsExceeded> {
+    sign_bounded_internal::<P>(sig, m, sk, None, max_attempts)
+}
+
+/// Hedged signing with an attempt budget, see `sign_bounded` and `sign_randomized`.
+pub fn sign_randomized_bounded<P: Mode, R: RngCore + CryptoRng>(
+    rng: &mut R,
+    sig: &mut P::Signature,
+    m: &[u8],
+    sk: &P::SecretKey,
+    max_attempts: u32
+) -> Result<SignStats, AttemptsExceeded> {
+    let mut rnd = [0; SEEDBYTES];
+    rng.fill_bytes(&mut rnd);
+    let r = sign_bounded_internal::<P>(sig, m, sk, Some(&rnd), max_attempts);
+    rnd.zeroize();
+    r
+}
+
+fn sign_bounded_internal<P: Mode>(
+    sig: &mut P::Signature,
+    m: &[u8],
+    sk: &P::SecretKey,
+    rnd: Option<&[u8; SEEDBYTES]>,
+    max_attempts: u32
+) -> Result<SignStats, AttemptsExceeded> {
+    let mut mu = [0; CRHBYTES];
+    let mut stats = SignStats::default();
+
+    // Compute CRH(tr, msg)
+    shake256!(&mut mu; &sk.as_ref()[2 * SEEDBYTES..][..CRHBYTES], m);
+    match sign_mu_budget::<P>(sig, &mu, sk, rnd, max_attempts, &mut stats) {
+        Ok(true) => Ok(stats),
+        Ok(false) => {
+            sig.zeroize();
+            Err(AttemptsExceeded(stats))
+        },
+        Err(err) => panic!("{}", err)
+    }
+}
+
+/// Secret key with the matrix expanded and `s1`, `s2`, `t0` in NTT domain,
+/// for signing many messages with the same key.
+///
+/// Signing with it skips unpacking and expansion and produces the same
+/// signatures as `sign` and `sign_randomized`. It is wiped on drop.
+pub struct ExpandedSecretKey<P: Mode> {
+    mat: P::Matrix,
+    s1: P::PolyVecL,
+    s2: P::PolyVecK,
+    t0: P::PolyVecK,
+    key: [u8; SEEDBYTES],
+    tr: [u8; CRHBYTES]
+}
+
+impl<P: Mode> ExpandedSecretKey<P> {
+    pub fn new(sk: &P::SecretKey) -> Self {
+        let mut esk = Self::empty();
+        assert!(esk.expand(sk), "secret key coefficient out of range");
+        esk
+    }
 
-    loop {
-        let mut c = [0; N];
+    /// `new`, returning an error instead of panicking on a secret key with
+    /// out of range `s1` or `s2` coefficients.
+    pub fn try_new(sk: &P::SecretKey) -> Result<Self, KeyError> {
+        let mut esk = Self::empty();
+        if esk.expand(sk) {
+            Ok(esk)
+        } else {
+            Err(KeyError::EtaOutOfRange)
+        }
+    }
 
-        // Sample intermediate vector
-        for i in 0..L {
-            poly::uniform_gamma1m1(&mut y[i], &key, &mu, nonce);
-            nonce += 1;
+    fn empty() -> Self {
+        ExpandedSecretKey {
+            mat: P::Matrix::default(),
+            s1: P::PolyVecL::default(),
+            s2: P::PolyVecK::default(),
+            t0: P::PolyVecK::default(),
+            key: [0; SEEDBYTES],
+            tr: [0; CRHBYTES]
         }
+    }
+
+    /// Unpack and expand `sk` into `self`, returning whether `s1` and `s2`
+    /// are in range.
+    fn expand(&mut self, sk: &P::SecretKey) -> bool {
+        let mut rho = [0; SEEDBYTES];
 
-        // Matrix-vector multiplicatio
-        let mut yhat = y.clone();
-        yhat.ntt();
-        for i in 0..K {
-            polyvec::pointwise_acc_invmontgomery(&mut w[i], &mat[i], &yhat);
-            poly::invntt_montgomery(&mut w[i]);
+        let valid = packing::sk::unpack_checked::<P>(sk, &mut rho, &mut self.key, &mut self.tr, &mut self.s1, &mut self.s2, &mut self.t0);
+        if !valid {
+            self.zeroize();
+            return false;
         }
 
-        // Decompose w and call the random oracle
-        w.csubq();
-        w.decompose(&mut tmp, &mut w1);
-        challenge(&mut c, &mu, &w1);
+        // Expand matrix and transform vectors
+        expand_mat::<P>(&mut self.mat, &rho);
+        self.s1.ntt();
+        self.s2.ntt();
+        self.t0.ntt();
+        true
+    }
 
-        // Compute z, reject if it reveals secret
-        let mut chat = c.clone();
-        poly::ntt(&mut chat);
-        for i in 0..L {
-            poly::pointwise_invmontgomery(&mut z[i], &chat, &s1[i]);
-            poly::invntt_montgomery(&mut z[i])
+    pub fn sign(&self, sig: &mut P::Signature, m: &[u8]) {
+        self.sign_internal(sig, m, None)
+    }
+
+    /// Hedged signing, see `sign_randomized`.
+    pub fn sign_randomized<R: RngCore + CryptoRng>(&self, rng: &mut R, sig: &mut P::Signature, m: &[u8]) {
+        let mut rnd = [0; SEEDBYTES];
+        rng.fill_bytes(&mut rnd);
+        self.sign_internal(sig, m, Some(&rnd));
+        rnd.zeroize();
+    }
+
+    fn sign_internal(&self, sig: &mut P::Signature, m: &[u8], rnd: Option<&[u8; SEEDBYTES]>) {
+        let mut mu = [0; CRHBYTES];
+
+        // Compute CRH(tr, msg)
+        shake256!(&mut mu; &self.tr, m);
+        let signed = self.sign_mu(sig, &mu, rnd, u32::MAX, &mut SignStats::default());
+        assert!(signed, "signing ran out of mask nonces");
+    }
+
+    fn sign_mu(
+        &self,
+        sig: &mut P::Signature,
+        mu: &[u8; CRHBYTES],
+        rnd: Option<&[u8; SEEDBYTES]>,
+        max_attempts: u32,
+        stats: &mut SignStats
+    ) -> bool {
+        let mut nonce = 0;
+        let mut signed = false;
+        let (mut y, mut yhat, mut z) =
+            (P::PolyVecL::default(), P::PolyVecL::default(), P::PolyVecL::default());
+        let (mut w, mut w1) = (P::PolyVecK::default(), P::PolyVecK::default());
+        let (mut h, mut wcs2, mut wcs20, mut ct0, mut tmp) =
+            (P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default(), P::PolyVecK::default());
+        let mut key = self.key;
+        let max_attempts = max_attempts.min(max_nonce_attempts::<P>());
+
+        // Hedged mode, derive the mask seed from key, fresh randomness and mu
+        if let Some(rnd) = rnd {
+            shake256!(&mut key; &key, rnd, mu);
         }
-        z.add_assign(&y);
-        z.freeze();
-        if z.chknorm(GAMMA1 - BETA) { continue };
-
-        // Compute w - cs2, reject if w1 can not be computed from it
-        for i in 0..K {
-            poly::pointwise_invmontgomery(&mut wcs20[i], &chat, &s2[i]);
-            poly::invntt_montgomery(&mut wcs20[i]);
+
+        while stats.attempts < max_attempts {
+            let mut c = [0; N];
+            stats.attempts += 1;
+
+            // Sample intermediate vector
+            for i in 0..P::L {
+                poly::uniform_gamma1m1(&mut y[i], &key, mu, nonce);
+                nonce += 1;
+            }
+
+            // Matrix-vector multiplication
+            yhat.clone_from(&y);
+            yhat.ntt();
+            for i in 0..P::K {
+                polyvec::pointwise_acc_invmontgomery(&mut w[i], &self.mat[i], &yhat);
+                poly::invntt_montgomery(&mut w[i]);
+            }
+
+            // Decompose w and call the random oracle
+            w.csubq();
+            w.decompose(&mut tmp, &mut w1);
+            challenge::<P>(&mut c, mu, &w1);
+
+            // Compute z, reject if it reveals secret
+            let mut chat = c;
+            poly::ntt(&mut chat);
+            for i in 0..P::L {
+                poly::pointwise_invmontgomery(&mut z[i], &chat, &self.s1[i]);
+                poly::invntt_montgomery(&mut z[i])
+            }
+            z.add_assign(&y);
+            z.freeze();
+            if z.chknorm(GAMMA1 - P::BETA) {
+                stats.z_norm += 1;
+                continue
+            }
+
+            // Compute w - cs2, reject if w1 can not be computed from it
+            for i in 0..P::K {
+                poly::pointwise_invmontgomery(&mut wcs20[i], &chat, &self.s2[i]);
+                poly::invntt_montgomery(&mut wcs20[i]);
+            }
+            wcs2.with_sub(&w, &wcs20);
+            wcs2.freeze();
+            wcs2.decompose(&mut wcs20, &mut tmp);
+            wcs20.csubq();
+            if wcs20.chknorm(GAMMA2 - P::BETA) {
+                stats.r0_norm += 1;
+                continue
+            }
+
+            if tmp != w1 {
+                stats.w1_mismatch += 1;
+                continue
+            }
+
+            // Compute hints for w1
+            for i in 0..P::K {
+                poly::pointwise_invmontgomery(&mut ct0[i], &chat, &self.t0[i]);
+                poly::invntt_montgomery(&mut ct0[i]);
+            }
+
+            ct0.csubq();
+            if ct0.chknorm(GAMMA2) {
+                stats.ct0_norm += 1;
+                continue
+            }
+
+            tmp.with_add(&wcs2, &ct0);
+            tmp.csubq();
+            let hint = polyvec::make_hint(&mut h, &wcs2, &tmp);
+            if hint > P::OMEGA {
+                stats.hint_count += 1;
+                continue
+            }
+
+            // Write signature
+            packing::sign::pack::<P>(sig, &z, &h, &c);
+
+            signed = true;
+            break
         }
-        wcs2.with_sub(&w, &wcs20);
-        wcs2.freeze();
-        wcs2.decompose(&mut wcs20, &mut tmp);
-        wcs20.csubq();
-        if wcs20.chknorm(GAMMA2 - BETA) { continue };
-
-        if tmp != w1 { continue };
-
-        // Compute hints for w1
-        for i in 0..K {
-            poly::pointwise_invmontgomery(&mut ct0[i], &chat, &t0[i]);
-            poly::invntt_montgomery(&mut ct0[i]);
+
+
+        // Wipe secret intermediates
+        key.zeroize();
+        y.zeroize();
+        yhat.zeroize();
+        z.zeroize();
+        w.zeroize();
+        wcs2.zeroize();
+        wcs20.zeroize();
+        ct0.zeroize();
+        tmp.zeroize();
+        signed
+    }
+}
+
+impl<P: Mode> Clone for ExpandedSecretKey<P> {
+    fn clone(&self) -> Self {
+        ExpandedSecretKey {
+            mat: self.mat,
+            s1: self.s1,
+            s2: self.s2,
+            t0: self.t0,
+            key: self.key,
+            tr: self.tr
         }
+    }
+}
+
+impl<P: Mode> fmt::Debug for ExpandedSecretKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ExpandedSecretKey(..)")
+    }
+}
+
+impl<P: Mode> Zeroize for ExpandedSecretKey<P> {
+    fn zeroize(&mut self) {
+        self.s1.zeroize();
+        self.s2.zeroize();
+        self.t0.zeroize();
+        self.key.zeroize();
+    }
+}
 
-        ct0.csubq();
-        if ct0.chknorm(GAMMA2) { continue };
+impl<P: Mode> Drop for ExpandedSecretKey<P> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+pub fn verify<P: Mode>(m: &[u8], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    verify_mu::<P>(&compute_mu::<P>(pk, m), sig, pk)
+}
 
-        tmp.with_add(&wcs2, &ct0);
+/// Compute the message representative `mu = CRH(CRH(rho, t1), m)` that is
+/// actually signed, for use with `sign_mu` and `verify_mu`.
+pub fn compute_mu<P: Mode>(pk: &P::PublicKey, m: &[u8]) -> [u8; CRHBYTES] {
+    let mut mu = [0; CRHBYTES];
+    shake256!(&mut mu; pk.as_ref());
+    shake256!(&mut mu; &mu, m);
+    mu
+}
+
+/// Verify a signature made by `sign_ctx` under the context string `ctx`.
+pub fn verify_ctx<P: Mode>(m: &[u8], ctx: &[u8], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    let mut tr = [0; CRHBYTES];
+    shake256!(&mut tr; pk.as_ref());
+    let mu = compute_mu_ctx(&tr, ctx, m).map_err(|_| VerifyError::ContextTooLong)?;
+    verify_mu::<P>(&mu, sig, pk)
+}
+
+/// Verify a signature over a message representative `mu` computed by `compute_mu`.
+pub fn verify_mu<P: Mode>(mu: &[u8; CRHBYTES], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    #[cfg(feature = "low-memory")]
+    {
+        ::lowmem::verify_mu::<P>(mu, sig, pk)
+    }
+
+    #[cfg(not(feature = "low-memory"))]
+    {
+        // Filled in place, see `sign_mu_budget`
+        let mut epk = ExpandedPublicKey::<P>::empty();
+        epk.expand(pk);
+        epk.verify_mu(mu, sig)
+    }
+}
+
+/// Attached signing, like `crypto_sign` of the NIST API: write `sig || m` to `sm`.
+///
+/// # Panics
+///
+/// If `sm` is not `P::BYTES + m.len()` bytes long.
+pub fn sign_message<P: Mode>(sm: &mut [u8], m: &[u8], sk: &P::SecretKey) {
+    attach::<P::Signature, _>(sm, m, |sig| sign::<P>(sig, m, sk))
+}
+
+/// Open an attached signature `sm = sig || m`, like `crypto_sign_open`,
+/// returning the message once the signature is verified.
+pub fn open<'a, P: Mode>(sm: &'a [u8], pk: &P::PublicKey) -> Result<&'a [u8], VerifyError> {
+    detach::<P::Signature, _>(sm, |m, sig| verify::<P>(m, sig, pk))
+}
+
+/// Write `sig || m` to `sm`, with `sig` produced by `sign`.
+/// Shared by the attached signing of every scheme.
+pub(crate) fn attach<S: Bytes, F: FnOnce(&mut S)>(sm: &mut [u8], m: &[u8], sign: F) {
+    let mut sig = S::zero();
+    let len = sig.as_ref().len();
+    assert_eq!(sm.len(), len + m.len());
+
+    sign(&mut sig);
+    sm[..len].copy_from_slice(sig.as_ref());
+    sm[len..].copy_from_slice(m);
+}
+
+/// Split `sm = sig || m` and return `m` once `verify` accepted `sig` over it.
+pub(crate) fn detach<S: Bytes, F: FnOnce(&[u8], &S) -> Result<(), VerifyError>>(sm: &[u8], verify: F) -> Result<&[u8], VerifyError> {
+    let mut sig = S::zero();
+    let len = sig.as_ref().len();
+    if sm.len() < len {
+        return Err(VerifyError::MalformedSignature);
+    }
+
+    let (sig_bytes, m) = sm.split_at(len);
+    sig.as_mut().copy_from_slice(sig_bytes);
+    verify(m, &sig)?;
+    Ok(m)
+}
+
+/// Verify a batch of `(message, signature, public key)` items.
+///
+/// Items sharing a public key reuse one `ExpandedPublicKey`. With the
+/// `rayon` feature the items are verified in parallel. On failure, returns
+/// the indices of all items that did not verify, in increasing order.
+#[cfg(feature = "std")]
+pub fn verify_batch<P: Mode>(items: &[(&[u8], &P::Signature, &P::PublicKey)]) -> Result<(), Vec<usize>> {
+    // Group items sharing a public key
+    let mut order = (0..items.len()).collect::<Vec<_>>();
+    order.sort_by(|&i, &j| items[i].2.as_ref().cmp(items[j].2.as_ref()));
+    let groups = order
+        .chunk_by(|&i, &j| items[i].2.as_ref() == items[j].2.as_ref())
+        .collect::<Vec<_>>();
+
+    let mut failed = verify_groups::<P>(items, &groups);
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        failed.sort_unstable();
+        Err(failed)
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "rayon")))]
+fn verify_groups<P: Mode>(items: &[(&[u8], &P::Signature, &P::PublicKey)], groups: &[&[usize]]) -> Vec<usize> {
+    let mut failed = Vec::new();
+    for group in groups {
+        let epk = ExpandedPublicKey::<P>::new(items[group[0]].2);
+        failed.extend(group.iter().cloned().filter(|&i| epk.verify(items[i].0, items[i].1).is_err()));
+    }
+    failed
+}
+
+#[cfg(feature = "rayon")]
+fn verify_groups<P: Mode>(items: &[(&[u8], &P::Signature, &P::PublicKey)], groups: &[&[usize]]) -> Vec<usize> {
+    use rayon::prelude::*;
+
+    groups.par_iter()
+        .flat_map(|group| {
+            let epk = ExpandedPublicKey::<P>::new(items[group[0]].2);
+            group.par_iter()
+                .cloned()
+                .filter(|&i| epk.verify(items[i].0, items[i].1).is_err())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Public key with the matrix expanded, `t1 * 2^D` in NTT domain and
+/// `CRH(pk)` precomputed, for verifying many signatures under the same key.
+pub struct ExpandedPublicKey<P: Mode> {
+    mat: P::Matrix,
+    t1: P::PolyVecK,
+    tr: [u8; CRHBYTES]
+}
+
+impl<P: Mode> ExpandedPublicKey<P> {
+    pub fn new(pk: &P::PublicKey) -> Self {
+        let mut epk = Self::empty();
+        epk.expand(pk);
+        epk
+    }
+
+    fn empty() -> Self {
+        ExpandedPublicKey {
+            mat: P::Matrix::default(),
+            t1: P::PolyVecK::default(),
+            tr: [0; CRHBYTES]
+        }
+    }
+
+    /// Unpack and expand `pk` into `self`.
+    fn expand(&mut self, pk: &P::PublicKey) {
+        let mut rho = [0; SEEDBYTES];
+
+        packing::pk::unpack::<P>(pk, &mut rho, &mut self.t1);
+        shake256!(&mut self.tr; pk.as_ref());
+
+        // Expand matrix and transform t1
+        expand_mat::<P>(&mut self.mat, &rho);
+        self.t1.shift_left(D as u32);
+        self.t1.ntt();
+    }
+
+    pub fn verify(&self, m: &[u8], sig: &P::Signature) -> Result<(), VerifyError> {
+        let mut mu = [0; CRHBYTES];
+
+        // Compute CRH(CRH(rho, t1), msg)
+        shake256!(&mut mu; &self.tr, m);
+        self.verify_mu(&mu, sig)
+    }
+
+    fn verify_mu(&self, mu: &[u8; CRHBYTES], sig: &P::Signature) -> Result<(), VerifyError> {
+        let (mut c, mut cp) = ([0; N], [0; N]);
+        let mut z = P::PolyVecL::default();
+        let (mut w1, mut h) = (P::PolyVecK::default(), P::PolyVecK::default());
+        let (mut tmp1, mut tmp2) = (P::PolyVecK::default(), P::PolyVecK::default());
+
+        let r = packing::sign::unpack::<P>(sig, &mut z, &mut h, &mut c);
+
+        if !r { return Err(VerifyError::MalformedSignature) };
+        if z.chknorm(GAMMA1 - P::BETA) { return Err(VerifyError::NormBoundExceeded) };
+
+        // Matrix-vector multiplication; compute Az - c2^dt1
+        z.ntt();
+        for i in 0..P::K {
+            polyvec::pointwise_acc_invmontgomery(&mut tmp1[i], &self.mat[i], &z);
+        }
+
+        let mut chat = c;
+        poly::ntt(&mut chat);
+        for i in 0..P::K {
+            poly::pointwise_invmontgomery(&mut tmp2[i], &chat, &self.t1[i]);
+        }
+
+        let mut tmp = P::PolyVecK::default();
+        tmp.with_sub(&tmp1, &tmp2);
+        tmp.reduce();
+        tmp.invntt_montgomery();
+
+        // Reconstruct w1
         tmp.csubq();
-        let hint = polyvec::make_hint(&mut h, &wcs2, &tmp);
-        if hint > OMEGA { continue };
+        polyvec::use_hint(&mut w1, &tmp, &h);
 
-        // Write signature
-        packing::sign::pack(sig, &z, &h, &c);
+        // Call random oracle and verify challenge
+        challenge::<P>(&mut cp, mu, &w1);
 
-        break
+        if c[..].ct_eq(&cp[..]).into() {
+            Ok(())
+        } else {
+            Err(VerifyError::ChallengeMismatch)
+        }
     }
 }
 
-pub fn verify(m: &[u8], sig: &[u8; SIG_SIZE_PACKED], pk: &[u8; PK_SIZE_PACKED]) -> bool {
-    let (mut rho, mut mu) = ([0; SEEDBYTES], [0; CRHBYTES]);
-    let (mut c, mut cp) = ([0; N], [0; N]);
-    let mut mat = [PolyVecL::default(); K];
-    let mut z = PolyVecL::default();
-    let (mut t1, mut w1, mut h) = Default::default();
-    let (mut tmp1, mut tmp2) = (PolyVecK::default(), PolyVecK::default());
+impl<P: Mode> Clone for ExpandedPublicKey<P> {
+    fn clone(&self) -> Self {
+        ExpandedPublicKey { mat: self.mat, t1: self.t1, tr: self.tr }
+    }
+}
 
-    packing::pk::unpack(pk, &mut rho, &mut t1);
-    let r = packing::sign::unpack(sig, &mut z, &mut h, &mut c);
+impl<P: Mode> fmt::Debug for ExpandedPublicKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ExpandedPublicKey").field(&&self.tr[..]).finish()
+    }
+}
 
-    if !r { return false };
-    if z.chknorm(GAMMA1 - BETA) { return false };
 
-    // TODO
-    // Compute CRH(CRH(rho, t1), msg)
-    shake256!(&mut mu; pk);
-    shake256!(&mut mu; &mu, m);
+/// Incremental signing of a message absorbed in chunks.
+///
+/// Feed the message with `update`, `digest::Input` or `std::io::Write`, then
+/// `finalize` into the same signature `sign` would produce for the whole
+/// message.
+pub struct SigningContext<'a, P: Mode> {
+    sk: &'a P::SecretKey,
+    hasher: Shake256
+}
 
-    // Matrix-vector multiplication; compute Az - c2^dt1
-    expand_mat(&mut mat, &rho);
-    z.ntt();
-    for i in 0..K {
-        polyvec::pointwise_acc_invmontgomery(&mut tmp1[i], &mat[i], &z);
+impl<'a, P: Mode> SigningContext<'a, P> {
+    pub fn new(sk: &'a P::SecretKey) -> Self {
+        let mut hasher = Shake256::default();
+        hasher.process(&sk.as_ref()[2 * SEEDBYTES..][..CRHBYTES]);
+        SigningContext { sk, hasher }
     }
 
-    let mut chat = c.clone();
-    poly::ntt(&mut chat);
-    t1.shift_left(D as u32);
-    t1.ntt();
-    for i in 0..K {
-        poly::pointwise_invmontgomery(&mut tmp2[i], &chat, &t1[i]);
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.process(data);
     }
 
-    let mut tmp = PolyVecK::default();
-    tmp.with_sub(&tmp1, &tmp2);
-    tmp.reduce();
-    tmp.invntt_montgomery();
+    pub fn finalize(self, sig: &mut P::Signature) {
+        let mu = finalize_mu(self.hasher);
+        expect_valid_key(sign_mu_internal::<P>(sig, &mu, self.sk, None));
+    }
 
-    // Reconstruct w1
-    tmp.csubq();
-    polyvec::use_hint(&mut w1, &tmp, &h);
+    pub fn finalize_randomized<R: RngCore + CryptoRng>(self, rng: &mut R, sig: &mut P::Signature) {
+        let mut rnd = [0; SEEDBYTES];
+        rng.fill_bytes(&mut rnd);
+        let mu = finalize_mu(self.hasher);
+        let r = sign_mu_internal::<P>(sig, &mu, self.sk, Some(&rnd));
+        rnd.zeroize();
+        expect_valid_key(r);
+    }
+}
+
+/// Incremental verification of a message absorbed in chunks.
+pub struct VerifyingContext<'a, P: Mode> {
+    pk: &'a P::PublicKey,
+    hasher: Shake256
+}
 
-    // Call random oracle and verify challenge
-    challenge(&mut cp, &mu, &w1);
+impl<'a, P: Mode> VerifyingContext<'a, P> {
+    pub fn new(pk: &'a P::PublicKey) -> Self {
+        let mut tr = [0; CRHBYTES];
+        shake256!(&mut tr; pk.as_ref());
 
-    // TODO use subtle
-    //  https://github.com/isislovecruft/subtle/pull/5
-    (0..N)
-        .map(|i| c[i] ^ cp[i])
-        .fold(0, |sum, next| sum | next)
-        .eq(&0)
+        let mut hasher = Shake256::default();
+        hasher.process(&tr);
+        VerifyingContext { pk, hasher }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.process(data);
+    }
+
+    pub fn finalize(self, sig: &P::Signature) -> Result<(), VerifyError> {
+        let mu = finalize_mu(self.hasher);
+        verify_mu::<P>(&mu, sig, self.pk)
+    }
 }
+
+fn finalize_mu(hasher: Shake256) -> [u8; CRHBYTES] {
+    let mut mu = [0; CRHBYTES];
+    hasher.xof_result().read(&mut mu);
+    mu
+}
+
+macro_rules! impl_streaming {
+    ( $name:ident ) => {
+        impl<'a, P: Mode> Input for $name<'a, P> {
+            fn process(&mut self, input: &[u8]) {
+                self.update(input);
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl<'a, P: Mode> ::std::io::Write for $name<'a, P> {
+            fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> ::std::io::Result<()> {
+                Ok(())
+            }
+        }
+    }
+}
+
+impl_streaming!(SigningContext);
+impl_streaming!(VerifyingContext);
@@ -0,0 +1,85 @@
+use super::*;
+use params::{ Mode, Mode0, Mode1, Mode2, Mode3, Bytes, N };
+use sign::{ VerifyError, FaultDetected, sign_hardened_internal };
+
+
+/// Flip the `j`-th sign of the challenge, which keeps its encoding valid.
+fn flip_c<P: Mode>(sig: &mut P::Signature, j: usize) {
+    let (_, _, c_bytes) = packing::sign::split_mut::<P>(sig.as_mut());
+    c_bytes[N / 8 + j / 8] ^= 1 << (j % 8);
+}
+
+/// Add or drop a hint of the last polynomial, keeping the encoding valid.
+fn flip_hint<P: Mode>(sig: &mut P::Signature) {
+    let (_, h_bytes, _) = packing::sign::split_mut::<P>(sig.as_mut());
+    let start = h_bytes[P::OMEGA + P::K - 2] as usize;
+    let end = h_bytes[P::OMEGA + P::K - 1] as usize;
+
+    if end < P::OMEGA && (end == start || h_bytes[end - 1] != 255) {
+        h_bytes[end] = 255;
+        h_bytes[P::OMEGA + P::K - 1] += 1;
+    } else {
+        h_bytes[end - 1] = 0;
+        h_bytes[P::OMEGA + P::K - 1] -= 1;
+    }
+}
+
+fn test_hardened<P: Mode>() {
+    for i in 0..4 {
+        let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+        let (mut sig, mut sig2) = (P::Signature::zero(), P::Signature::zero());
+        sign::keypair_from_seed::<P>(&[i; 32], &mut pk, &mut sk);
+
+        // The recomputed r0 and ct0 pass for every genuine signature
+        sign::sign::<P>(&mut sig, b"message", &sk);
+        assert_eq!(sign_hardened_internal::<P, _>(&mut sig2, b"message", &sk, None, |_| ()), Ok(()));
+        assert!(sig.as_ref() == sig2.as_ref());
+
+        // z out of its bound
+        for b in sig2.as_mut() { *b = 0xa5 }
+        let r = sign_hardened_internal::<P, _>(&mut sig2, b"message", &sk, None, |sig| {
+            for b in &mut sig.as_mut()[..5] { *b = 0 }
+        });
+        assert_eq!(r, Err(FaultDetected::NormRecheck));
+        assert!(sig2.as_ref().iter().all(|&b| b == 0xa5));
+
+        // A faulted challenge leaves z alone, but makes r0, the low bits of
+        // A z - c t, close to uniform, so it only fails the recheck most of
+        // the time. Verification catches the rest.
+        let mut rechecked = 0;
+        for j in 0..16 {
+            let r = sign_hardened_internal::<P, _>(&mut sig2, b"message", &sk, None, |sig| flip_c::<P>(sig, j));
+            match r {
+                Err(FaultDetected::NormRecheck) => rechecked += 1,
+                r => assert_eq!(r, Err(FaultDetected::Verify(VerifyError::ChallengeMismatch)))
+            }
+            assert!(sig2.as_ref().iter().all(|&b| b == 0xa5));
+        }
+        assert!(rechecked > 0);
+
+        // A faulted hint passes the norm checks, but not verification
+        let r = sign_hardened_internal::<P, _>(&mut sig2, b"message", &sk, None, flip_hint::<P>);
+        assert_eq!(r, Err(FaultDetected::Verify(VerifyError::ChallengeMismatch)));
+        assert!(sig2.as_ref().iter().all(|&b| b == 0xa5));
+    }
+}
+
+#[test]
+fn test_hardened_mode0() {
+    test_hardened::<Mode0>();
+}
+
+#[test]
+fn test_hardened_mode1() {
+    test_hardened::<Mode1>();
+}
+
+#[test]
+fn test_hardened_mode2() {
+    test_hardened::<Mode2>();
+}
+
+#[test]
+fn test_hardened_mode3() {
+    test_hardened::<Mode3>();
+}
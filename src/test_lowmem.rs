@@ -0,0 +1,79 @@
+extern crate rand;
+
+use super::*;
+use params::{ Mode, Mode0, Mode1, Mode2, Mode3, Bytes, SEEDBYTES, CRHBYTES };
+use sign::{ VerifyError, KeyError, SignStats };
+use self::rand::{ RngCore, thread_rng };
+
+
+fn sign_lowmem<P: Mode>(sig: &mut P::Signature, m: &[u8], sk: &P::SecretKey, rnd: Option<&[u8; SEEDBYTES]>) {
+    let mut mu = [0; CRHBYTES];
+    shake256!(&mut mu; &sk.as_ref()[2 * SEEDBYTES..][..CRHBYTES], m);
+    assert_eq!(lowmem::sign_mu::<P>(sig, &mu, sk, rnd, u32::MAX, &mut SignStats::default()), Ok(true));
+}
+
+fn verify_lowmem<P: Mode>(m: &[u8], sig: &P::Signature, pk: &P::PublicKey) -> Result<(), VerifyError> {
+    lowmem::verify_mu::<P>(&sign::compute_mu::<P>(pk, m), sig, pk)
+}
+
+fn test_lowmem<P: Mode>() {
+    let mut rng = thread_rng();
+
+    for _ in 0..10 {
+        let mut seed = [0; 32];
+        let mut message = [0; 59];
+        rng.fill_bytes(&mut seed);
+        rng.fill_bytes(&mut message);
+
+        let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+        let (mut sig, mut sig2) = (P::Signature::zero(), P::Signature::zero());
+        sign::keypair_from_seed::<P>(&seed, &mut pk, &mut sk);
+
+        // Same signature as the default path, even into a dirty buffer
+        sign::sign::<P>(&mut sig, &message, &sk);
+        rng.fill_bytes(sig2.as_mut());
+        sign_lowmem::<P>(&mut sig2, &message, &sk, None);
+        assert!(sig.as_ref() == sig2.as_ref());
+
+        assert_eq!(verify_lowmem::<P>(&message, &sig, &pk), Ok(()));
+        sign_lowmem::<P>(&mut sig2, &message, &sk, Some(&seed));
+        assert_eq!(verify_lowmem::<P>(&message, &sig2, &pk), Ok(()));
+
+        message[0] ^= 1;
+        assert_eq!(verify_lowmem::<P>(&message, &sig, &pk), Err(VerifyError::ChallengeMismatch));
+        message[0] ^= 1;
+
+        let mut bad = sig;
+        bad.as_mut()[P::BYTES - 1] = 0xff;
+        assert_eq!(verify_lowmem::<P>(&message, &bad, &pk), Err(VerifyError::MalformedSignature));
+
+        let mut bad = sig;
+        for b in &mut bad.as_mut()[..5] { *b = 0 }
+        assert_eq!(verify_lowmem::<P>(&message, &bad, &pk), Err(VerifyError::NormBoundExceeded));
+
+        let mut bad = sk;
+        bad.as_mut()[2 * SEEDBYTES + CRHBYTES] = 0xff;
+        let r = lowmem::sign_mu::<P>(&mut sig2, &[0; CRHBYTES], &bad, None, u32::MAX, &mut SignStats::default());
+        assert_eq!(r, Err(KeyError::EtaOutOfRange));
+    }
+}
+
+#[test]
+fn test_lowmem_mode0() {
+    test_lowmem::<Mode0>();
+}
+
+#[test]
+fn test_lowmem_mode1() {
+    test_lowmem::<Mode1>();
+}
+
+#[test]
+fn test_lowmem_mode2() {
+    test_lowmem::<Mode2>();
+}
+
+#[test]
+fn test_lowmem_mode3() {
+    test_lowmem::<Mode3>();
+}
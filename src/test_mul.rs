@@ -19,7 +19,7 @@ fn poly_naivemul(c: &mut Poly, a: &Poly, b: &Poly) {
     }
 
     for i in N..(2 * N) {
-        r[i - N] = r[i - N] + (Q as u32) - r[i];
+        r[i - N] = r[i - N] + Q - r[i];
         r[i - N] %= Q;
     }
 
@@ -35,9 +35,9 @@ fn test_mul() {
 
     for _ in 0..NTESTS {
         thread_rng().fill_bytes(&mut rndbuf);
-        poly::uniform(&mut a, &rndbuf);
+        assert_eq!(poly::rej_uniform(&mut a, &rndbuf), N);
         thread_rng().fill_bytes(&mut rndbuf);
-        poly::uniform(&mut b, &rndbuf);
+        assert_eq!(poly::rej_uniform(&mut b, &rndbuf), N);
 
         poly_naivemul(&mut c1, &a, &b);
 
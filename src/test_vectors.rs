@@ -1,20 +1,27 @@
 extern crate hex;
 
+use std::vec::Vec;
 use self::hex::FromHexError;
 use super::*;
 use byteorder::{ ByteOrder, BigEndian };
 use itertools::Itertools;
-use polyvec::{ PolyVecL, PolyVecK };
+use polyvec::PolyVec;
 use params::{
-    N, K, L,
+    Mode, Mode2, N,
     SEEDBYTES, CRHBYTES
 };
 
+type PolyVecL = <Mode2 as Mode>::PolyVecL;
+type PolyVecK = <Mode2 as Mode>::PolyVecK;
+type Matrix = <Mode2 as Mode>::Matrix;
+const K: usize = Mode2::K;
+const L: usize = Mode2::L;
+
 const TEST_VECTORS: &str = include_str!("../tests/testvectors.txt");
 
 struct TestVector {
     seed: ([u8; SEEDBYTES], [u8; CRHBYTES]),
-    mat: [PolyVecL; K],
+    mat: Matrix,
     s: PolyVecL,
     y: PolyVecL,
     w1: PolyVecK,
@@ -25,7 +32,7 @@ impl Default for TestVector {
     fn default() -> Self {
         TestVector {
             seed: ([0; SEEDBYTES], [0; CRHBYTES]),
-            mat: [PolyVecL::default(); K],
+            mat: Matrix::default(),
             s: PolyVecL::default(),
             y: PolyVecL::default(),
             w1: PolyVecK::default(),
@@ -42,7 +49,7 @@ fn parse_testvectors() -> Result<Vec<TestVector>, FromHexError> {
 
         for (key, val) in testvector
             .map(|line| line.split('='))
-            .map(|mut split| (split.next(), split.last()))
+            .map(|mut split| (split.next(), split.next_back()))
             .filter_map(|(key, val)| key.and_then(|key| val.map(|val| (key.trim(), val.trim()))))
         {
             match key {
@@ -115,7 +122,7 @@ fn parse_testvectors() -> Result<Vec<TestVector>, FromHexError> {
 #[test]
 fn test_vectors() {
     for tv in parse_testvectors().unwrap() {
-        let mut mat = [PolyVecL::default(); K];
+        let mut mat = Matrix::default();
         let mut s = PolyVecL::default();
         let mut y = PolyVecL::default();
         let mut w = PolyVecK::default();
@@ -123,18 +130,18 @@ fn test_vectors() {
         let mut tmp = PolyVecK::default();
         let mut c = [0; N];
 
-        sign::expand_mat(&mut mat, &tv.seed.0);
-        assert!(&mat == &tv.mat);
+        sign::expand_mat::<Mode2>(&mut mat, &tv.seed.0);
+        assert!(mat == tv.mat);
 
         for i in 0..L {
-            poly::uniform_eta(&mut s[i], &tv.seed.0, i as u8);
+            poly::uniform_eta::<Mode2>(&mut s[i], &tv.seed.0, i as u8);
         }
-        assert!(&s == &tv.s);
+        assert!(s == tv.s);
 
         for i in 0..L {
             poly::uniform_gamma1m1(&mut y[i], &tv.seed.0, &tv.seed.1, i as u16);
         }
-        assert!(&y == &tv.y);
+        assert!(y == tv.y);
 
         y.ntt();
         for i in 0..K {
@@ -143,9 +150,9 @@ fn test_vectors() {
         }
         w.csubq();
         w.decompose(&mut tmp, &mut w1);
-        assert!(&w1 == &tv.w1);
+        assert!(w1 == tv.w1);
 
-        sign::challenge(&mut c, &tv.seed.1, &w1);
-        assert!(&c[..] == &tv.c[..]);
+        sign::challenge::<Mode2>(&mut c, &tv.seed.1, &w1);
+        assert!(c[..] == tv.c[..]);
     }
 }
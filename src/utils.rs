@@ -1,13 +1,5 @@
-macro_rules! shake128 {
-    ( $output:expr ; $( $input:expr ),* ) => {
-        let mut hasher = ::sha3::Shake128::default();
-        $(
-            ::digest::Input::process(&mut hasher, $input);
-        )*
-        let mut reader = ::digest::ExtendableOutput::xof_result(hasher);
-        ::digest::XofReader::read(&mut reader, $output);
-    }
-}
+use subtle::{ Choice, ConstantTimeEq };
+
 
 macro_rules! shake256 {
     ( $output:expr ; $( $input:expr ),* ) => {
@@ -19,3 +11,13 @@ macro_rules! shake256 {
         ::digest::XofReader::read(&mut reader, $output);
     }
 }
+
+/// Compare two byte strings without branching on their contents.
+pub fn ct_eq(x: &[u8], y: &[u8]) -> bool {
+    x.ct_eq(y).into()
+}
+
+/// `Choice` that is set when `x` is negative.
+pub fn ct_is_negative(x: i32) -> Choice {
+    Choice::from((x as u32 >> 31) as u8)
+}
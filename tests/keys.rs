@@ -0,0 +1,135 @@
+extern crate rand;
+extern crate zeroize;
+extern crate dilithium;
+
+use rand::{ FromEntropy, ChaChaRng };
+use zeroize::Zeroize;
+use dilithium::params::{ Mode, Mode1, Mode3 };
+use dilithium::keys::{ self, PublicKey, SecretKey, SecretSeed, Signature, Error };
+use dilithium::sign::KeyError;
+
+
+#[test]
+fn test_typed_sign() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (pk, sk) = keys::keypair::<Mode3, _>(&mut rng);
+
+    let sig = sk.sign(b"message");
+    assert!(pk.verify(b"message", &sig).is_ok());
+    assert!(pk.verify(b"messagf", &sig).is_err());
+
+    let sig = sk.sign_randomized(&mut rng, b"message");
+    assert!(pk.verify(b"message", &sig).is_ok());
+
+    let sig = sk.sign_hardened(b"message").unwrap();
+    assert_eq!(sig, sk.sign(b"message"));
+}
+
+#[test]
+fn test_from_bytes() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (pk, sk) = keys::keypair::<Mode1, _>(&mut rng);
+    let sig = sk.sign(b"message");
+
+    let pk2 = PublicKey::<Mode1>::from_bytes(pk.as_bytes()).unwrap();
+    let sk2 = SecretKey::<Mode1>::from_bytes(sk.as_bytes()).unwrap();
+    let sig2 = Signature::<Mode1>::from_bytes(sig.as_bytes()).unwrap();
+    assert_eq!(pk, pk2);
+    assert_eq!(sk, sk2);
+    assert_eq!(sig, sig2);
+    assert!(pk2.verify(b"message", &sig2).is_ok());
+
+    assert_eq!(PublicKey::<Mode3>::from_bytes(pk.as_bytes()).unwrap_err(), Error::InvalidLength);
+    assert_eq!(SecretKey::<Mode1>::from_bytes(&sk.as_bytes()[1..]).unwrap_err(), Error::InvalidLength);
+
+    // An s1 coefficient outside [-ETA, ETA]
+    let mut bytes = sk.as_bytes().to_vec();
+    bytes[2 * 32 + 48] = 0xff;
+    assert_eq!(SecretKey::<Mode1>::from_bytes(&bytes).unwrap_err(), Error::InvalidKey(KeyError::EtaOutOfRange));
+
+    // A z coefficient of -GAMMA1 or less
+    let mut bytes = sig.as_bytes().to_vec();
+    for b in &mut bytes[..5] { *b = 0xff }
+    assert_eq!(Signature::<Mode1>::from_bytes(&bytes).unwrap_err(), Error::InvalidEncoding);
+
+    // A non-zero sign bit beyond the 60 used ones
+    let mut bytes = sig.as_bytes().to_vec();
+    *bytes.last_mut().unwrap() |= 0x80;
+    assert_eq!(Signature::<Mode1>::from_bytes(&bytes).unwrap_err(), Error::InvalidEncoding);
+
+    // Hint counter past OMEGA
+    let mut bytes = sig.as_bytes().to_vec();
+    let pos = Mode1::BYTES - (dilithium::params::N / 8 + 8) - 1;
+    bytes[pos] = 0xff;
+    assert_eq!(Signature::<Mode1>::from_bytes(&bytes).unwrap_err(), Error::InvalidEncoding);
+}
+
+#[test]
+fn test_debug_hides_secret() {
+    let (_, sk) = keys::keypair_from_seed::<Mode1>(&[1; 32]);
+    assert_eq!(format!("{:?}", sk), "SecretKey(..)");
+}
+
+#[test]
+fn test_zeroize_secret() {
+    let (_, mut sk) = keys::keypair_from_seed::<Mode1>(&[1; 32]);
+    assert!(sk.as_bytes().iter().any(|&b| b != 0));
+    sk.zeroize();
+    assert!(sk.as_bytes().iter().all(|&b| b == 0));
+
+    let mut seed = SecretSeed::<Mode1>::from_bytes(&[1; 32]).unwrap();
+    seed.zeroize();
+    assert!(seed.as_bytes().iter().all(|&b| b == 0));
+}
+
+#[test]
+fn test_public_key_from_secret() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (pk, sk) = keys::keypair::<Mode3, _>(&mut rng);
+    assert_eq!(sk.public_key(), Ok(pk.clone()));
+
+    // Corrupted tr
+    let mut bytes = sk.as_bytes().to_vec();
+    bytes[2 * 32] ^= 1;
+    assert_eq!(SecretKey::<Mode3>::from_bytes_unchecked(&bytes).unwrap().public_key(), Err(KeyError::TrMismatch));
+
+    // Corrupted t0, the last part of the secret key
+    let mut bytes = sk.as_bytes().to_vec();
+    *bytes.last_mut().unwrap() ^= 1;
+    assert_eq!(SecretKey::<Mode3>::from_bytes_unchecked(&bytes).unwrap().public_key(), Err(KeyError::T0Mismatch));
+}
+
+#[test]
+fn test_secret_seed() {
+    let mut rng = ChaChaRng::from_entropy();
+    let seed = SecretSeed::<Mode3>::generate(&mut rng);
+    assert_eq!(seed.as_bytes().len(), 32);
+
+    let keypair = seed.expand();
+    let sk = SecretKey::from(&seed);
+    assert_eq!(&sk, keypair.secret());
+    assert!(seed.matches(&sk));
+    assert!(!SecretSeed::<Mode3>::generate(&mut rng).matches(&sk));
+
+    let seed2 = SecretSeed::<Mode3>::from_bytes(seed.as_bytes()).unwrap();
+    assert_eq!(seed, seed2);
+    assert_eq!(seed2.expand().public(), keypair.public());
+    assert_eq!(SecretSeed::<Mode3>::from_bytes(&[0; 31]).unwrap_err(), Error::InvalidLength);
+    assert_eq!(format!("{:?}", seed), "SecretSeed(..)");
+}
+
+#[test]
+fn test_from_bytes_validates() {
+    let (_, sk) = keys::keypair_from_seed::<Mode1>(&[2; 32]);
+    assert_eq!(SecretKey::<Mode1>::from_bytes(sk.as_bytes()), Ok(sk.clone()));
+
+    let mut bytes = sk.as_bytes().to_vec();
+    bytes[2 * 32] ^= 1;
+    assert_eq!(SecretKey::<Mode1>::from_bytes(&bytes), Err(Error::InvalidKey(KeyError::TrMismatch)));
+    assert!(SecretKey::<Mode1>::from_bytes_unchecked(&bytes).is_ok());
+
+    let mut bytes = sk.as_bytes().to_vec();
+    *bytes.last_mut().unwrap() ^= 1;
+    assert_eq!(SecretKey::<Mode1>::from_bytes(&bytes), Err(Error::InvalidKey(KeyError::T0Mismatch)));
+    assert_eq!(SecretKey::<Mode1>::from_bytes_unchecked(&bytes[1..]), Err(Error::InvalidLength));
+}
@@ -0,0 +1,207 @@
+extern crate rand;
+extern crate hex;
+extern crate sha3;
+extern crate digest;
+extern crate dilithium;
+
+use rand::{ RngCore, FromEntropy, ChaChaRng };
+use digest::Digest;
+use sha3::{ Sha3_256, Sha3_512, Shake128, Shake256 };
+use dilithium::params::Bytes;
+use dilithium::sign::VerifyError;
+use dilithium::mldsa::{
+    Params, MlDsa, MlDsa44, MlDsa65, MlDsa87, ContextTooLong, PreHash, PreHashError,
+    keypair, keypair_from_seed, sign, sign_deterministic, verify,
+    sign_prehash, sign_prehash_deterministic, verify_prehash,
+    sign_digest, sign_digest_deterministic, verify_digest
+};
+use dilithium::mldsa::prehash::{ OID_SHA256, OID_SHA512, OID_SHA3_256 };
+
+
+fn test_sign<P: MlDsa>() {
+    for _ in 0..50 {
+        let mut rng = ChaChaRng::from_entropy();
+        let mut message = [0; 59];
+        let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+        let mut sig = P::Signature::zero();
+        rng.fill_bytes(&mut message);
+
+        keypair::<P, _>(&mut rng, &mut pk, &mut sk);
+        sign::<P, _>(&mut rng, &mut sig, &message, b"context", &sk).unwrap();
+
+        assert!(verify::<P>(&message, b"context", &sig, &pk).is_ok());
+        assert!(verify::<P>(&message, b"", &sig, &pk).is_err());
+
+        message[2] ^= 42;
+        assert!(verify::<P>(&message, b"context", &sig, &pk).is_err());
+    }
+}
+
+/// Expected SHA3-256 of the public key, secret key and deterministic signature of
+/// `"message"` under context `"ctx"`, for the key generated from seed `0, 1, .., 31`.
+///
+/// The digests match the ML-DSA of OpenSSL 3.5, and can be regenerated with
+///
+/// ```sh
+/// openssl genpkey -algorithm ML-DSA-44 -pkeyopt hexseed:000102..1f -out sk.pem
+/// openssl pkeyutl -sign -rawin -in msg -inkey sk.pem \
+///     -pkeyopt deterministic:1 -pkeyopt context-string:ctx -out sig
+/// ```
+///
+/// where the raw keys are the trailing `PUBLICKEYBYTES` of `openssl pkey -pubout -outform DER`
+/// and `SECRETKEYBYTES` of `openssl pkey -outform DER`.
+fn test_kat<P: MlDsa>(expected: [&str; 3]) {
+    let mut seed = [0; 32];
+    for (i, b) in seed.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+
+    let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+    let mut sig = P::Signature::zero();
+
+    keypair_from_seed::<P>(&seed, &mut pk, &mut sk);
+    sign_deterministic::<P>(&mut sig, b"message", b"ctx", &sk).unwrap();
+
+    assert_eq!(hex::encode(Sha3_256::digest(pk.as_ref())), expected[0]);
+    assert_eq!(hex::encode(Sha3_256::digest(sk.as_ref())), expected[1]);
+    assert_eq!(hex::encode(Sha3_256::digest(sig.as_ref())), expected[2]);
+    assert!(verify::<P>(b"message", b"ctx", &sig, &pk).is_ok());
+}
+
+#[test]
+fn test_sign_mldsa44() {
+    test_sign::<MlDsa44>();
+}
+
+#[test]
+fn test_sign_mldsa65() {
+    test_sign::<MlDsa65>();
+}
+
+#[test]
+fn test_sign_mldsa87() {
+    test_sign::<MlDsa87>();
+}
+
+#[test]
+fn test_kat_mldsa44() {
+    test_kat::<MlDsa44>([
+        "373c7bf2cac5bd2a6c35933bab0fa1c951f22247e1333383fcb618822080373f",
+        "da0665a6d807669d06aeb7010507aa6bd4640641055462e38e37344a274f36a6",
+        "2b3ac76f6305c911e46f5539b50b715dc3d3ed7baa2b36d89cfdf79195d264cc"
+    ]);
+}
+
+#[test]
+fn test_kat_mldsa65() {
+    test_kat::<MlDsa65>([
+        "1800725067e388d837d911fe4f66101cc1961b1bb755030dc574272cfb00013f",
+        "4ecfbd119980b1090a7feda39c225539ffa3ef3ff673b239b60189bfd4541170",
+        "da590599f8d156add635778374b145082b1bf6dcd2f05534ef4c539fc4ae12c4"
+    ]);
+}
+
+#[test]
+fn test_kat_mldsa87() {
+    test_kat::<MlDsa87>([
+        "e6cf50a9c2fa5234f59949ff61f8161db4d629532127f4aefa8bb10811ecfb1e",
+        "db6218a2eeae53e567d25222055251eab0ba85d1c69aeb8a62253d2096fd96a2",
+        "db4aa2e7acf105569febf32283865d5536a472cb45c8fce2d1f19adc6bf4fb41"
+    ]);
+}
+
+#[test]
+fn test_context_too_long() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; MlDsa44::PUBLICKEYBYTES], [0; MlDsa44::SECRETKEYBYTES]);
+    let mut sig = [0; MlDsa44::BYTES];
+    let ctx = [0; 256];
+
+    keypair::<MlDsa44, _>(&mut rng, &mut pk, &mut sk);
+    assert_eq!(sign_deterministic::<MlDsa44>(&mut sig, b"message", &ctx, &sk), Err(ContextTooLong));
+    assert!(sign_deterministic::<MlDsa44>(&mut sig, b"message", &ctx[..255], &sk).is_ok());
+    assert!(verify::<MlDsa44>(b"message", &ctx[..255], &sig, &pk).is_ok());
+    assert_eq!(verify::<MlDsa44>(b"message", &ctx, &sig, &pk), Err(VerifyError::ContextTooLong));
+}
+
+#[test]
+fn test_prehash() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; MlDsa65::PUBLICKEYBYTES], [0; MlDsa65::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; MlDsa65::BYTES], [0; MlDsa65::BYTES]);
+    keypair::<MlDsa65, _>(&mut rng, &mut pk, &mut sk);
+
+    fn hasher<H: PreHash>(m: &[u8]) -> H {
+        let mut h = H::default();
+        h.process(m);
+        h
+    }
+
+    sign_prehash::<MlDsa65, Sha3_256, _>(&mut rng, &mut sig, hasher(b"message"), b"ctx", &sk).unwrap();
+    assert_eq!(verify_prehash::<MlDsa65, Sha3_256>(hasher(b"message"), b"ctx", &sig, &pk), Ok(()));
+    assert!(verify_prehash::<MlDsa65, Sha3_256>(hasher(b"message"), b"", &sig, &pk).is_err());
+    assert!(verify_prehash::<MlDsa65, Sha3_256>(hasher(b"messagf"), b"ctx", &sig, &pk).is_err());
+
+    // Bound to the hash function, not only the digest bytes
+    let digest = hasher::<Sha3_256>(b"message").finish();
+    assert_eq!(verify_digest::<MlDsa65>(OID_SHA3_256, &digest, b"ctx", &sig, &pk), Ok(()));
+    assert!(verify_digest::<MlDsa65>(OID_SHA256, &digest, b"ctx", &sig, &pk).is_err());
+    assert!(verify_prehash::<MlDsa65, Shake128>(hasher(b"message"), b"ctx", &sig, &pk).is_err());
+
+    sign_prehash::<MlDsa65, Sha3_512, _>(&mut rng, &mut sig, hasher(b"message"), b"ctx", &sk).unwrap();
+    assert_eq!(verify_prehash::<MlDsa65, Sha3_512>(hasher(b"message"), b"ctx", &sig, &pk), Ok(()));
+    sign_prehash::<MlDsa65, Shake128, _>(&mut rng, &mut sig, hasher(b"message"), b"ctx", &sk).unwrap();
+    assert_eq!(verify_prehash::<MlDsa65, Shake128>(hasher(b"message"), b"ctx", &sig, &pk), Ok(()));
+    sign_prehash::<MlDsa65, Shake256, _>(&mut rng, &mut sig, hasher(b"message"), b"ctx", &sk).unwrap();
+    assert_eq!(verify_prehash::<MlDsa65, Shake256>(hasher(b"message"), b"ctx", &sig, &pk), Ok(()));
+
+    // Digests from elsewhere, here a made up SHA-512 one
+    sign_digest::<MlDsa65, _>(&mut rng, &mut sig, OID_SHA512, &[7; 64], b"ctx", &sk).unwrap();
+    assert_eq!(verify_digest::<MlDsa65>(OID_SHA512, &[7; 64], b"ctx", &sig, &pk), Ok(()));
+    assert_eq!(verify_digest::<MlDsa65>(OID_SHA256, &[7; 64], b"ctx", &sig, &pk), Err(VerifyError::DigestLengthMismatch));
+
+    // A digest not as long as the output of the named hash function is refused
+    assert_eq!(sign_digest::<MlDsa65, _>(&mut rng, &mut sig2, OID_SHA512, &[7; 32], b"ctx", &sk), Err(PreHashError::DigestLengthMismatch));
+    assert_eq!(sign_digest_deterministic::<MlDsa65>(&mut sig2, OID_SHA256, &[7; 64], b"ctx", &sk), Err(PreHashError::DigestLengthMismatch));
+    assert_eq!(verify_digest::<MlDsa65>(OID_SHA512, &[7; 32], b"ctx", &sig, &pk), Err(VerifyError::DigestLengthMismatch));
+
+    // Nor can the check be sidestepped by moving OID bytes into the digest
+    let mut split = vec![OID_SHA256[10]];
+    split.extend_from_slice(&[7; 64]);
+    assert_eq!(sign_digest::<MlDsa65, _>(&mut rng, &mut sig2, &OID_SHA256[..10], &split, b"ctx", &sk), Err(PreHashError::MalformedOid));
+    assert_eq!(verify_digest::<MlDsa65>(&OID_SHA256[..10], &split, b"ctx", &sig, &pk), Err(VerifyError::MalformedOid));
+    assert_eq!(sign_digest_deterministic::<MlDsa65>(&mut sig2, &[], &[7; 64], b"ctx", &sk), Err(PreHashError::MalformedOid));
+
+    // Other well-formed OIDs are taken as they are
+    let oid = [0x06, 0x03, 0x2a, 0x03, 0x04];
+    sign_digest_deterministic::<MlDsa65>(&mut sig2, &oid, &[7; 48], b"ctx", &sk).unwrap();
+    assert_eq!(verify_digest::<MlDsa65>(&oid, &[7; 48], b"ctx", &sig2, &pk), Ok(()));
+
+    // Same as the trait based API
+    sign_prehash_deterministic::<MlDsa65, Sha3_256>(&mut sig, hasher(b"message"), b"ctx", &sk).unwrap();
+    sign_digest_deterministic::<MlDsa65>(&mut sig2, OID_SHA3_256, &digest, b"ctx", &sk).unwrap();
+    assert!(sig[..] == sig2[..]);
+
+    // Separated from pure signing of the encoded digest
+    let mut m = Sha3_256::oid().to_vec();
+    m.extend_from_slice(&digest);
+    sign_deterministic::<MlDsa65>(&mut sig, &m, b"ctx", &sk).unwrap();
+    assert!(sig[..] != sig2[..]);
+    assert!(verify_prehash::<MlDsa65, Sha3_256>(hasher(b"message"), b"ctx", &sig, &pk).is_err());
+    assert!(verify::<MlDsa65>(&m, b"ctx", &sig2, &pk).is_err());
+
+    assert_eq!(Sha3_256::oid(), [0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x08]);
+    assert_eq!(sign_prehash_deterministic::<MlDsa65, Sha3_256>(&mut sig, hasher(b"message"), &[0; 256], &sk), Err(PreHashError::ContextTooLong));
+    assert_eq!(verify_digest::<MlDsa65>(OID_SHA3_256, &digest, &[0; 256], &sig2, &pk), Err(VerifyError::ContextTooLong));
+}
+
+#[test]
+#[should_panic(expected = "secret key coefficient out of range")]
+fn test_sign_eta_out_of_range() {
+    let (mut pk, mut sk) = ([0; MlDsa44::PUBLICKEYBYTES], [0; MlDsa44::SECRETKEYBYTES]);
+    let mut sig = [0; MlDsa44::BYTES];
+    keypair_from_seed::<MlDsa44>(&[3; 32], &mut pk, &mut sk);
+
+    sk[2 * 32 + 64] = 0xff;
+    let _ = sign_deterministic::<MlDsa44>(&mut sig, b"message", b"", &sk);
+}
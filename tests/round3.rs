@@ -0,0 +1,108 @@
+extern crate rand;
+extern crate dilithium;
+
+use rand::{ RngCore, FromEntropy, ChaChaRng };
+use dilithium::params::Bytes;
+use dilithium::mldsa::Params;
+use dilithium::round3::{
+    Round3, Dilithium2, Dilithium3, Dilithium5,
+    keypair, keypair_from_seed, sign, sign_randomized, verify, sign_message, open
+};
+
+
+fn test_sign<P: Round3>() {
+    for _ in 0..50 {
+        let mut rng = ChaChaRng::from_entropy();
+        let mut message = [0; 59];
+        let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+        let mut sig = P::Signature::zero();
+        rng.fill_bytes(&mut message);
+
+        keypair::<P, _>(&mut rng, &mut pk, &mut sk);
+        sign::<P>(&mut sig, &message, &sk);
+
+        assert!(verify::<P>(&message, &sig, &pk).is_ok());
+
+        message[2] ^= 42;
+        assert!(verify::<P>(&message, &sig, &pk).is_err());
+    }
+}
+
+#[test]
+fn test_sizes() {
+    assert_eq!((Dilithium2::PUBLICKEYBYTES, Dilithium2::SECRETKEYBYTES, Dilithium2::BYTES), (1312, 2528, 2420));
+    assert_eq!((Dilithium3::PUBLICKEYBYTES, Dilithium3::SECRETKEYBYTES, Dilithium3::BYTES), (1952, 4000, 3293));
+    assert_eq!((Dilithium5::PUBLICKEYBYTES, Dilithium5::SECRETKEYBYTES, Dilithium5::BYTES), (2592, 4864, 4595));
+}
+
+#[test]
+fn test_sign_dilithium2() {
+    test_sign::<Dilithium2>();
+}
+
+#[test]
+fn test_sign_dilithium3() {
+    test_sign::<Dilithium3>();
+}
+
+#[test]
+fn test_sign_dilithium5() {
+    test_sign::<Dilithium5>();
+}
+
+#[test]
+fn test_deterministic() {
+    let seed = [7; 32];
+    let (mut pk, mut sk) = ([0; Dilithium2::PUBLICKEYBYTES], [0; Dilithium2::SECRETKEYBYTES]);
+    let (mut pk2, mut sk2) = ([0; Dilithium2::PUBLICKEYBYTES], [0; Dilithium2::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; Dilithium2::BYTES], [0; Dilithium2::BYTES]);
+
+    keypair_from_seed::<Dilithium2>(&seed, &mut pk, &mut sk);
+    keypair_from_seed::<Dilithium2>(&seed, &mut pk2, &mut sk2);
+    assert!(pk[..] == pk2[..] && sk[..] == sk2[..]);
+
+    sign::<Dilithium2>(&mut sig, b"message", &sk);
+    sign::<Dilithium2>(&mut sig2, b"message", &sk2);
+    assert!(sig[..] == sig2[..]);
+    assert!(verify::<Dilithium2>(b"message", &sig, &pk2).is_ok());
+}
+
+#[test]
+fn test_sign_randomized() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; Dilithium3::PUBLICKEYBYTES], [0; Dilithium3::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; Dilithium3::BYTES], [0; Dilithium3::BYTES]);
+
+    keypair::<Dilithium3, _>(&mut rng, &mut pk, &mut sk);
+    sign_randomized::<Dilithium3, _>(&mut rng, &mut sig, b"message", &sk);
+    sign_randomized::<Dilithium3, _>(&mut rng, &mut sig2, b"message", &sk);
+
+    assert!(verify::<Dilithium3>(b"message", &sig, &pk).is_ok());
+    assert!(verify::<Dilithium3>(b"message", &sig2, &pk).is_ok());
+    assert!(sig[..] != sig2[..]);
+}
+
+#[test]
+fn test_sign_message_open() {
+    let (mut pk, mut sk) = ([0; Dilithium2::PUBLICKEYBYTES], [0; Dilithium2::SECRETKEYBYTES]);
+    keypair_from_seed::<Dilithium2>(&[5; 32], &mut pk, &mut sk);
+
+    let mut sm = vec![0; Dilithium2::BYTES + 7];
+    sign_message::<Dilithium2>(&mut sm, b"message", &sk);
+    assert_eq!(open::<Dilithium2>(&sm, &pk), Ok(&b"message"[..]));
+
+    sm[Dilithium2::BYTES] ^= 1;
+    assert!(open::<Dilithium2>(&sm, &pk).is_err());
+    assert!(open::<Dilithium2>(&sm[..10], &pk).is_err());
+}
+
+#[test]
+#[should_panic(expected = "secret key coefficient out of range")]
+fn test_sign_eta_out_of_range() {
+    let (mut pk, mut sk) = ([0; Dilithium2::PUBLICKEYBYTES], [0; Dilithium2::SECRETKEYBYTES]);
+    let mut sig = [0; Dilithium2::BYTES];
+    keypair_from_seed::<Dilithium2>(&[3; 32], &mut pk, &mut sk);
+
+    sk[2 * 32 + 32] = 0xff;
+    sign::<Dilithium2>(&mut sig, b"message", &sk);
+}
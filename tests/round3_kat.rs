@@ -0,0 +1,68 @@
+//! Known answer tests for the first entry, `count = 0`, of the `.rsp` files
+//! generated by `PQCgenKAT_sign` for the round 3 (v3.1) reference.
+//!
+//! The expected values come from the PQClean `dilithium{2,3,5}/clean`
+//! implementations, which are v3.1 as shipped in `pqcrypto-dilithium` 0.4.6,
+//! run through the NIST AES-256 CTR DRBG `randombytes`. `XI` is the 32 byte
+//! key generation seed the DRBG yields after being seeded with the `seed` of
+//! entry 0, and the digests are SHA3-256 of the `pk`, `sk` and `sm` fields of
+//! `PQCsignKAT_2528.rsp`, `PQCsignKAT_4000.rsp` and `PQCsignKAT_4864.rsp`.
+
+extern crate hex;
+extern crate sha3;
+extern crate digest;
+extern crate dilithium;
+
+use digest::Digest;
+use sha3::Sha3_256;
+use dilithium::params::Bytes;
+use dilithium::round3::{ Round3, Dilithium2, Dilithium3, Dilithium5, keypair_from_seed, sign_message, open };
+
+
+const XI: &str = "7c9935a0b07694aa0c6d10e4db6b1add2fd81a25ccb148032dcd739936737f2d";
+const MSG: &str = "d81c4d8d734fcbfbeade3d3f8a039faa2a2c9957e835ad55b22e75bf57bb556ac8";
+
+/// Expected SHA3-256 of `pk`, `sk` and `sm` of entry 0.
+fn test_kat<P: Round3>(expected: [&str; 3]) {
+    let mut xi = [0; 32];
+    xi.copy_from_slice(&hex::decode(XI).unwrap());
+    let msg = hex::decode(MSG).unwrap();
+
+    let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+    keypair_from_seed::<P>(&xi, &mut pk, &mut sk);
+
+    let mut sm = vec![0; P::BYTES + msg.len()];
+    sign_message::<P>(&mut sm, &msg, &sk);
+
+    assert_eq!(hex::encode(Sha3_256::digest(pk.as_ref())), expected[0]);
+    assert_eq!(hex::encode(Sha3_256::digest(sk.as_ref())), expected[1]);
+    assert_eq!(hex::encode(Sha3_256::digest(&sm)), expected[2]);
+    assert_eq!(open::<P>(&sm, &pk), Ok(&msg[..]));
+}
+
+#[test]
+fn test_kat_dilithium2() {
+    test_kat::<Dilithium2>([
+        "3965ec9c424d72743aeb461873fe1ef579742444e7bddd1bcbe8939a27e95b8c",
+        "4b752f9a786fd581bca02e81237fff4b68fda2df6be88796cd970cc0aebb64d2",
+        "5f693e42df1c46ffc5e9a50055a6370c95daa4037bd5d148bb59c2ab438ea021"
+    ]);
+}
+
+#[test]
+fn test_kat_dilithium3() {
+    test_kat::<Dilithium3>([
+        "b3a8bfe06ce9c75208c94caf2494462779fd61edfd98f546ed8a97141a43bef3",
+        "4e313f50e09d123942efe69993efa519ff30c8cd44d71d5ba17e73afbd474394",
+        "3c6c5bed89c05a4548a8c31929e10462a29cda2a8594dfb6cc27a62327240bf6"
+    ]);
+}
+
+#[test]
+fn test_kat_dilithium5() {
+    test_kat::<Dilithium5>([
+        "4cb13352c600d29720c556928528e1429f9de7b5c1e5a7e3918c5bc0b4502a33",
+        "9831a3fc26f812ee1932c125a4805154adbc2da0d7a3ce4c6d085d8cc6b92ca0",
+        "0faba9edcc06194ec8e0b7856b1b7cb1e77f51be008eda14e2a3bf54f374b04d"
+    ]);
+}
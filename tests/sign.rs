@@ -1,26 +1,460 @@
 extern crate rand;
 extern crate dilithium;
 
-use rand::{ RngCore, FromEntropy, ChaChaRng };
-use dilithium::params::*;
-use dilithium::sign::{ keypair, sign, verify };
+use rand::{ RngCore, SeedableRng, FromEntropy, ChaChaRng };
+use dilithium::params::{ Mode, Mode0, Mode1, Mode2, Mode3, Bytes };
+use dilithium::sign::{
+    keypair, keypair_from_seed, sign, sign_randomized, verify, VerifyError, sign_checked, sign_randomized_checked,
+    ExpandedSecretKey, ExpandedPublicKey, sign_message, open,
+    SigningContext, VerifyingContext, compute_mu, sign_mu, sign_mu_randomized, verify_mu,
+    sign_ctx, sign_randomized_ctx, verify_ctx, public_key_from_secret,
+    validate_secret_key, KeyError, sign_bounded, sign_randomized_bounded, AttemptsExceeded,
+    sign_hardened, sign_randomized_hardened, FaultDetected
+};
+use dilithium::sign::ContextTooLong;
+#[cfg(feature = "std")]
+use dilithium::sign::verify_batch;
 
 
-#[test]
-fn test_sign() {
-    for _ in 0..500 {
+fn test_sign<P: Mode>(count: usize) {
+    for _ in 0..count {
         let mut rng = ChaChaRng::from_entropy();
         let mut message = [0; 59];
-        let (mut pk, mut sk) = ([0; PUBLICKEYBYTES], [0; SECRETKEYBYTES]);
-        let mut sig = [0; BYTES];
+        let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+        let mut sig = P::Signature::zero();
         rng.fill_bytes(&mut message);
 
-        keypair(&mut rng, &mut pk, &mut sk);
-        sign(&mut sig, &message, &sk);
+        keypair::<P, _>(&mut rng, &mut pk, &mut sk);
+        sign::<P>(&mut sig, &message, &sk);
 
-        assert!(verify(&message, &sig, &pk));
+        assert!(verify::<P>(&message, &sig, &pk).is_ok());
 
         message[2] ^= 42;
-        assert!(!verify(&message, &sig, &pk));
+        assert!(verify::<P>(&message, &sig, &pk).is_err());
+    }
+}
+
+#[test]
+fn test_sign_mode0() {
+    test_sign::<Mode0>(100);
+}
+
+#[test]
+fn test_sign_mode1() {
+    test_sign::<Mode1>(100);
+}
+
+#[test]
+fn test_sign_mode2() {
+    test_sign::<Mode2>(500);
+}
+
+#[test]
+fn test_sign_mode3() {
+    test_sign::<Mode3>(100);
+}
+
+#[test]
+fn test_mixed_modes() {
+    let mut rng = ChaChaRng::from_entropy();
+    let message = b"one process, several security levels";
+    let (mut pk2, mut sk2) = ([0; Mode2::PUBLICKEYBYTES], [0; Mode2::SECRETKEYBYTES]);
+    let (mut pk3, mut sk3) = ([0; Mode3::PUBLICKEYBYTES], [0; Mode3::SECRETKEYBYTES]);
+    let (mut sig2, mut sig3) = ([0; Mode2::BYTES], [0; Mode3::BYTES]);
+
+    keypair::<Mode2, _>(&mut rng, &mut pk2, &mut sk2);
+    keypair::<Mode3, _>(&mut rng, &mut pk3, &mut sk3);
+    sign::<Mode2>(&mut sig2, message, &sk2);
+    sign::<Mode3>(&mut sig3, message, &sk3);
+
+    assert!(verify::<Mode2>(message, &sig2, &pk2).is_ok());
+    assert!(verify::<Mode3>(message, &sig3, &pk3).is_ok());
+}
+
+#[test]
+fn test_keypair_from_seed() {
+    let mut seed = [0; 32];
+    ChaChaRng::from_entropy().fill_bytes(&mut seed);
+
+    let (mut pk, mut sk) = ([0; Mode2::PUBLICKEYBYTES], [0; Mode2::SECRETKEYBYTES]);
+    let (mut pk2, mut sk2) = ([0; Mode2::PUBLICKEYBYTES], [0; Mode2::SECRETKEYBYTES]);
+    let mut sig = [0; Mode2::BYTES];
+
+    keypair_from_seed::<Mode2>(&seed, &mut pk, &mut sk);
+    keypair_from_seed::<Mode2>(&seed, &mut pk2, &mut sk2);
+    assert!(pk[..] == pk2[..]);
+    assert!(sk[..] == sk2[..]);
+
+    // `keypair` consumes exactly one seed from the RNG
+    let mut rng = ChaChaRng::from_seed([42; 32]);
+    keypair::<Mode2, _>(&mut rng, &mut pk2, &mut sk2);
+    let mut rng = ChaChaRng::from_seed([42; 32]);
+    rng.fill_bytes(&mut seed);
+    keypair_from_seed::<Mode2>(&seed, &mut pk, &mut sk);
+    assert!(pk[..] == pk2[..]);
+    assert!(sk[..] == sk2[..]);
+
+    sign::<Mode2>(&mut sig, b"message", &sk);
+    assert!(verify::<Mode2>(b"message", &sig, &pk2).is_ok());
+}
+
+#[test]
+fn test_sign_randomized() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; Mode2::PUBLICKEYBYTES], [0; Mode2::SECRETKEYBYTES]);
+    let (mut sig, mut sig2, mut sig3) = ([0; Mode2::BYTES], [0; Mode2::BYTES], [0; Mode2::BYTES]);
+
+    keypair::<Mode2, _>(&mut rng, &mut pk, &mut sk);
+    sign_randomized::<Mode2, _>(&mut rng, &mut sig, b"message", &sk);
+    sign_randomized::<Mode2, _>(&mut rng, &mut sig2, b"message", &sk);
+    sign::<Mode2>(&mut sig3, b"message", &sk);
+
+    assert!(verify::<Mode2>(b"message", &sig, &pk).is_ok());
+    assert!(verify::<Mode2>(b"message", &sig2, &pk).is_ok());
+    assert!(verify::<Mode2>(b"messagf", &sig, &pk).is_err());
+    assert!(sig[..] != sig2[..]);
+    assert!(sig[..] != sig3[..]);
+}
+
+#[test]
+fn test_verify_errors() {
+    let (mut pk, mut sk) = ([0; Mode2::PUBLICKEYBYTES], [0; Mode2::SECRETKEYBYTES]);
+    let mut sig = [0; Mode2::BYTES];
+    keypair_from_seed::<Mode2>(&[7; 32], &mut pk, &mut sk);
+    sign::<Mode2>(&mut sig, b"message", &sk);
+
+    assert_eq!(verify::<Mode2>(b"messagf", &sig, &pk), Err(VerifyError::ChallengeMismatch));
+
+    let mut sig2 = sig;
+    sig2[Mode2::BYTES - 1] = 0xff;
+    assert_eq!(verify::<Mode2>(b"message", &sig2, &pk), Err(VerifyError::MalformedSignature));
+
+    let mut sig2 = sig;
+    for b in &mut sig2[..5] { *b = 0 }
+    assert_eq!(verify::<Mode2>(b"message", &sig2, &pk), Err(VerifyError::NormBoundExceeded));
+}
+
+#[test]
+fn test_expanded_secret_key() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; Mode3::PUBLICKEYBYTES], [0; Mode3::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; Mode3::BYTES], [0; Mode3::BYTES]);
+    keypair::<Mode3, _>(&mut rng, &mut pk, &mut sk);
+
+    let esk = ExpandedSecretKey::<Mode3>::new(&sk);
+    for i in 0..10u8 {
+        let message = [i; 33];
+        sign::<Mode3>(&mut sig, &message, &sk);
+        esk.sign(&mut sig2, &message);
+        assert!(sig[..] == sig2[..]);
+
+        esk.sign_randomized(&mut rng, &mut sig2, &message);
+        assert!(verify::<Mode3>(&message, &sig2, &pk).is_ok());
+    }
+}
+
+#[test]
+fn test_expanded_public_key() {
+    let (mut pk, mut sk) = ([0; Mode1::PUBLICKEYBYTES], [0; Mode1::SECRETKEYBYTES]);
+    let mut sig = [0; Mode1::BYTES];
+    keypair_from_seed::<Mode1>(&[3; 32], &mut pk, &mut sk);
+
+    let epk = ExpandedPublicKey::<Mode1>::new(&pk);
+    for i in 0..10u8 {
+        let message = [i; 33];
+        sign::<Mode1>(&mut sig, &message, &sk);
+        assert_eq!(epk.verify(&message, &sig), Ok(()));
+        assert_eq!(epk.verify(&[i + 1; 33], &sig), Err(VerifyError::ChallengeMismatch));
     }
+
+    sig[Mode1::BYTES - 1] = 0xff;
+    assert_eq!(epk.verify(&[9; 33], &sig), Err(VerifyError::MalformedSignature));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_verify_batch() {
+    let mut keys = Vec::new();
+    for i in 0..3 {
+        let (mut pk, mut sk) = ([0; Mode0::PUBLICKEYBYTES], [0; Mode0::SECRETKEYBYTES]);
+        keypair_from_seed::<Mode0>(&[i; 32], &mut pk, &mut sk);
+        keys.push((pk, sk));
+    }
+
+    let messages = (0..12u8).map(|i| [i; 17]).collect::<Vec<_>>();
+    let mut sigs = messages.iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let mut sig = [0; Mode0::BYTES];
+            sign::<Mode0>(&mut sig, m, &keys[i % 3].1);
+            sig
+        })
+        .collect::<Vec<_>>();
+
+    {
+        let items = (0..12)
+            .map(|i| (&messages[i][..], &sigs[i], &keys[i % 3].0))
+            .collect::<Vec<_>>();
+        assert_eq!(verify_batch::<Mode0>(&items), Ok(()));
+        assert_eq!(verify_batch::<Mode0>(&[]), Ok(()));
+    }
+
+    sigs[7][0] ^= 1;
+    sigs[2][Mode0::BYTES - 1] = 0xff;
+    let items = (0..12)
+        .map(|i| (&messages[i][..], &sigs[i], &keys[i % 3].0))
+        .collect::<Vec<_>>();
+    assert_eq!(verify_batch::<Mode0>(&items), Err(vec![2, 7]));
+}
+
+#[test]
+fn test_sign_message_open() {
+    let (mut pk, mut sk) = ([0; Mode2::PUBLICKEYBYTES], [0; Mode2::SECRETKEYBYTES]);
+    let mut sig = [0; Mode2::BYTES];
+    keypair_from_seed::<Mode2>(&[5; 32], &mut pk, &mut sk);
+
+    let mut sm = vec![0; Mode2::BYTES + 7];
+    sign_message::<Mode2>(&mut sm, b"message", &sk);
+    sign::<Mode2>(&mut sig, b"message", &sk);
+    assert!(sm[..Mode2::BYTES] == sig[..]);
+    assert_eq!(open::<Mode2>(&sm, &pk), Ok(&b"message"[..]));
+
+    sm[Mode2::BYTES] ^= 1;
+    assert_eq!(open::<Mode2>(&sm, &pk), Err(VerifyError::ChallengeMismatch));
+    assert_eq!(open::<Mode2>(&sm[..Mode2::BYTES - 1], &pk), Err(VerifyError::MalformedSignature));
+}
+
+#[test]
+fn test_streaming_context() {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut message = [0; 300];
+    let (mut pk, mut sk) = ([0; Mode3::PUBLICKEYBYTES], [0; Mode3::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; Mode3::BYTES], [0; Mode3::BYTES]);
+    rng.fill_bytes(&mut message);
+    keypair::<Mode3, _>(&mut rng, &mut pk, &mut sk);
+
+    sign::<Mode3>(&mut sig, &message, &sk);
+    let mut ctx = SigningContext::<Mode3>::new(&sk);
+    for chunk in message.chunks(7) {
+        ctx.update(chunk);
+    }
+    ctx.finalize(&mut sig2);
+    assert!(sig[..] == sig2[..]);
+
+    let mut ctx = VerifyingContext::<Mode3>::new(&pk);
+    ctx.update(&message[..100]);
+    ctx.update(&message[100..]);
+    assert_eq!(ctx.finalize(&sig), Ok(()));
+
+    let mut ctx = SigningContext::<Mode3>::new(&sk);
+    ctx.update(&message);
+    ctx.finalize_randomized(&mut rng, &mut sig2);
+    assert_eq!(verify::<Mode3>(&message, &sig2, &pk), Ok(()));
+
+    let mut ctx = VerifyingContext::<Mode3>::new(&pk);
+    ctx.update(&message[1..]);
+    assert_eq!(ctx.finalize(&sig), Err(VerifyError::ChallengeMismatch));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_streaming_context_write() {
+    use std::io::Write;
+
+    let (mut pk, mut sk) = ([0; Mode1::PUBLICKEYBYTES], [0; Mode1::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; Mode1::BYTES], [0; Mode1::BYTES]);
+    keypair_from_seed::<Mode1>(&[7; 32], &mut pk, &mut sk);
+
+    sign::<Mode1>(&mut sig, b"streamed message", &sk);
+    let mut ctx = SigningContext::<Mode1>::new(&sk);
+    ctx.write_all(b"streamed ").unwrap();
+    ctx.write_all(b"message").unwrap();
+    ctx.finalize(&mut sig2);
+    assert!(sig[..] == sig2[..]);
+
+    let mut ctx = VerifyingContext::<Mode1>::new(&pk);
+    ctx.write_all(b"streamed message").unwrap();
+    assert_eq!(ctx.finalize(&sig), Ok(()));
+}
+
+#[test]
+fn test_external_mu() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; Mode2::PUBLICKEYBYTES], [0; Mode2::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; Mode2::BYTES], [0; Mode2::BYTES]);
+    keypair::<Mode2, _>(&mut rng, &mut pk, &mut sk);
+
+    let mu = compute_mu::<Mode2>(&pk, b"message");
+    sign::<Mode2>(&mut sig, b"message", &sk);
+    sign_mu::<Mode2>(&mut sig2, &mu, &sk);
+    assert!(sig[..] == sig2[..]);
+    assert_eq!(verify_mu::<Mode2>(&mu, &sig, &pk), Ok(()));
+
+    sign_mu_randomized::<Mode2, _>(&mut rng, &mut sig2, &mu, &sk);
+    assert_eq!(verify::<Mode2>(b"message", &sig2, &pk), Ok(()));
+
+    let mu = compute_mu::<Mode2>(&pk, b"messagf");
+    assert_eq!(verify_mu::<Mode2>(&mu, &sig, &pk), Err(VerifyError::ChallengeMismatch));
+}
+
+#[test]
+fn test_context() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; Mode1::PUBLICKEYBYTES], [0; Mode1::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; Mode1::BYTES], [0; Mode1::BYTES]);
+    keypair::<Mode1, _>(&mut rng, &mut pk, &mut sk);
+
+    sign_ctx::<Mode1>(&mut sig, b"message", b"protocol a", &sk).unwrap();
+    assert_eq!(verify_ctx::<Mode1>(b"message", b"protocol a", &sig, &pk), Ok(()));
+    assert_eq!(verify_ctx::<Mode1>(b"message", b"protocol b", &sig, &pk), Err(VerifyError::ChallengeMismatch));
+    assert!(verify::<Mode1>(b"message", &sig, &pk).is_err());
+
+    // An empty context is still distinct from no context at all
+    sign_ctx::<Mode1>(&mut sig, b"message", b"", &sk).unwrap();
+    sign::<Mode1>(&mut sig2, b"message", &sk);
+    assert!(sig[..] != sig2[..]);
+    assert!(verify_ctx::<Mode1>(b"message", b"", &sig2, &pk).is_err());
+
+    // Plain signing of the encoded context and message does not carry over
+    sign::<Mode1>(&mut sig2, b"\x00\x03abcmessage", &sk);
+    assert_eq!(verify_ctx::<Mode1>(b"message", b"abc", &sig2, &pk), Err(VerifyError::ChallengeMismatch));
+    sign_ctx::<Mode1>(&mut sig, b"message", b"abc", &sk).unwrap();
+    assert_eq!(verify::<Mode1>(b"\x00\x03abcmessage", &sig, &pk), Err(VerifyError::ChallengeMismatch));
+
+    sign_randomized_ctx::<Mode1, _>(&mut rng, &mut sig, b"message", b"protocol a", &sk).unwrap();
+    assert_eq!(verify_ctx::<Mode1>(b"message", b"protocol a", &sig, &pk), Ok(()));
+
+    assert_eq!(sign_ctx::<Mode1>(&mut sig, b"message", &[0; 256], &sk), Err(ContextTooLong));
+    assert!(sign_ctx::<Mode1>(&mut sig, b"message", &[0; 255], &sk).is_ok());
+    assert_eq!(verify_ctx::<Mode1>(b"message", &[0; 255], &sig, &pk), Ok(()));
+    assert_eq!(verify_ctx::<Mode1>(b"message", &[0; 256], &sig, &pk), Err(VerifyError::ContextTooLong));
+}
+
+fn test_public_key_from_secret<P: Mode>() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = (P::PublicKey::zero(), P::SecretKey::zero());
+    let mut pk2 = P::PublicKey::zero();
+    keypair::<P, _>(&mut rng, &mut pk, &mut sk);
+
+    assert_eq!(public_key_from_secret::<P>(&sk, &mut pk2), Ok(()));
+    assert!(pk.as_ref() == pk2.as_ref());
+    assert_eq!(validate_secret_key::<P>(&sk), Ok(()));
+
+    // Packed s1 and s2 values above 2 ETA
+    let s_offset = 2 * 32 + 48;
+    for &pos in &[s_offset, s_offset + (P::L + P::K) * P::POLETA_SIZE_PACKED - 1] {
+        let mut bad = sk;
+        bad.as_mut()[pos] = 0xff;
+        assert_eq!(validate_secret_key::<P>(&bad), Err(KeyError::EtaOutOfRange));
+    }
+
+    // The output is left untouched on every error
+    let sk_len = sk.as_ref().len();
+    for &(pos, val, err) in &[
+        (s_offset, 0xff, KeyError::EtaOutOfRange),
+        (sk_len - 1, sk.as_ref()[sk_len - 1] ^ 1, KeyError::T0Mismatch),
+        (2 * 32, sk.as_ref()[2 * 32] ^ 1, KeyError::TrMismatch)
+    ] {
+        let mut bad = sk;
+        bad.as_mut()[pos] = val;
+        let mut out = P::PublicKey::zero();
+        out.as_mut().iter_mut().for_each(|b| *b = 0xa5);
+        assert_eq!(public_key_from_secret::<P>(&bad, &mut out), Err(err));
+        assert!(out.as_ref().iter().all(|&b| b == 0xa5));
+    }
+}
+
+#[test]
+fn test_public_key_from_secret_modes() {
+    test_public_key_from_secret::<Mode0>();
+    test_public_key_from_secret::<Mode1>();
+    test_public_key_from_secret::<Mode2>();
+    test_public_key_from_secret::<Mode3>();
+}
+
+#[test]
+fn test_sign_bounded() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; Mode2::PUBLICKEYBYTES], [0; Mode2::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; Mode2::BYTES], [0; Mode2::BYTES]);
+    let mut message = [0; 59];
+    keypair::<Mode2, _>(&mut rng, &mut pk, &mut sk);
+
+    let mut retried = false;
+    for _ in 0..20 {
+        rng.fill_bytes(&mut message);
+        sign::<Mode2>(&mut sig, &message, &sk);
+        let stats = sign_bounded::<Mode2>(&mut sig2, &message, &sk, 1000).unwrap();
+        assert!(sig[..] == sig2[..]);
+        assert!(stats.attempts >= 1);
+        assert_eq!(stats.attempts - 1, stats.z_norm + stats.r0_norm + stats.w1_mismatch + stats.ct0_norm + stats.hint_count);
+
+        // Not enough attempts for this message
+        if stats.attempts > 1 {
+            retried = true;
+            let err = sign_bounded::<Mode2>(&mut sig2, &message, &sk, stats.attempts - 1).unwrap_err();
+            assert_eq!(err.0.attempts, stats.attempts - 1);
+            assert!(sig2.iter().all(|&b| b == 0));
+        }
+    }
+    assert!(retried);
+
+    assert_eq!(sign_bounded::<Mode2>(&mut sig, &message, &sk, 0), Err(AttemptsExceeded(Default::default())));
+
+    let stats = sign_randomized_bounded::<Mode2, _>(&mut rng, &mut sig, &message, &sk, 1000).unwrap();
+    assert!(stats.attempts >= 1);
+    assert_eq!(verify::<Mode2>(&message, &sig, &pk), Ok(()));
+}
+
+#[test]
+fn test_sign_hardened() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; Mode3::PUBLICKEYBYTES], [0; Mode3::SECRETKEYBYTES]);
+    let (mut sig, mut sig2) = ([0; Mode3::BYTES], [0; Mode3::BYTES]);
+    keypair::<Mode3, _>(&mut rng, &mut pk, &mut sk);
+
+    sign::<Mode3>(&mut sig, b"message", &sk);
+    assert_eq!(sign_hardened::<Mode3>(&mut sig2, b"message", &sk), Ok(()));
+    assert!(sig[..] == sig2[..]);
+
+    assert_eq!(sign_randomized_hardened::<Mode3, _>(&mut rng, &mut sig2, b"message", &sk), Ok(()));
+    assert_eq!(verify::<Mode3>(b"message", &sig2, &pk), Ok(()));
+
+    // Corrupted keys are caught before anything is released
+    let mut bad = sk;
+    bad[2 * 32 + 48] = 0xff;
+    let mut sig3 = [0; Mode3::BYTES];
+    assert_eq!(sign_hardened::<Mode3>(&mut sig3, b"message", &bad), Err(FaultDetected::InvalidKey(KeyError::EtaOutOfRange)));
+    let mut bad = sk;
+    bad[Mode3::SECRETKEYBYTES - 1] ^= 1;
+    assert_eq!(sign_hardened::<Mode3>(&mut sig3, b"message", &bad), Err(FaultDetected::InvalidKey(KeyError::T0Mismatch)));
+    assert!(sig3.iter().all(|&b| b == 0));
+}
+
+#[test]
+#[should_panic(expected = "secret key coefficient out of range")]
+fn test_sign_eta_out_of_range() {
+    let (mut pk, mut sk) = ([0; Mode1::PUBLICKEYBYTES], [0; Mode1::SECRETKEYBYTES]);
+    let mut sig = [0; Mode1::BYTES];
+    keypair_from_seed::<Mode1>(&[3; 32], &mut pk, &mut sk);
+
+    sk[2 * 32 + 48] = 0xff;
+    sign::<Mode1>(&mut sig, b"message", &sk);
+}
+
+#[test]
+fn test_sign_checked() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (mut pk, mut sk) = ([0; Mode1::PUBLICKEYBYTES], [0; Mode1::SECRETKEYBYTES]);
+    let mut sig = [0; Mode1::BYTES];
+    keypair_from_seed::<Mode1>(&[3; 32], &mut pk, &mut sk);
+
+    assert_eq!(sign_checked::<Mode1>(&mut sig, b"message", &sk), Ok(()));
+    assert_eq!(verify::<Mode1>(b"message", &sig, &pk), Ok(()));
+    assert!(ExpandedSecretKey::<Mode1>::try_new(&sk).is_ok());
+
+    sk[2 * 32 + 48] = 0xff;
+    assert_eq!(sign_checked::<Mode1>(&mut sig, b"message", &sk), Err(KeyError::EtaOutOfRange));
+    assert_eq!(sign_randomized_checked::<Mode1, _>(&mut rng, &mut sig, b"message", &sk), Err(KeyError::EtaOutOfRange));
+    assert_eq!(ExpandedSecretKey::<Mode1>::try_new(&sk).unwrap_err(), KeyError::EtaOutOfRange);
 }
@@ -0,0 +1,80 @@
+#![cfg(feature = "signature")]
+
+extern crate rand;
+extern crate signature;
+extern crate dilithium;
+
+use rand::{ FromEntropy, ChaChaRng };
+use signature::{ Signer, Verifier, RandomizedSigner, Keypair as _ };
+use signature::rand_core::{ self, CryptoRng, RngCore };
+use dilithium::params::Mode2;
+use dilithium::keys::{ Keypair, SecretKey, Signature };
+
+
+/// `rand_core` 0.6 view of a `rand` 0.5 RNG.
+struct TestRng(ChaChaRng);
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        rand::RngCore::next_u32(&mut self.0)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand::RngCore::next_u64(&mut self.0)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand::RngCore::fill_bytes(&mut self.0, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for TestRng {}
+
+fn sign_generic<S: Signer<Signature<Mode2>>>(signer: &S, msg: &[u8]) -> Signature<Mode2> {
+    signer.sign(msg)
+}
+
+fn verify_generic<V: Verifier<Signature<Mode2>>>(verifier: &V, msg: &[u8], sig: &Signature<Mode2>) -> bool {
+    verifier.verify(msg, sig).is_ok()
+}
+
+#[test]
+fn test_signature_traits() {
+    let mut rng = ChaChaRng::from_entropy();
+    let keypair = Keypair::<Mode2>::generate(&mut rng);
+    let pk = keypair.verifying_key();
+
+    let sig = sign_generic(keypair.secret(), b"message");
+    assert!(verify_generic(&pk, b"message", &sig));
+    assert!(verify_generic(&keypair, b"message", &sig));
+    assert!(!verify_generic(&pk, b"messagf", &sig));
+
+    let sig = sign_generic(&keypair, b"message");
+    assert!(verify_generic(&pk, b"message", &sig));
+
+    let mut rng = TestRng(rng);
+    let sig = keypair.sign_with_rng(&mut rng, b"message");
+    let sig2 = keypair.secret().sign_with_rng(&mut rng, b"message");
+    assert!(sig != sig2);
+    assert!(verify_generic(&pk, b"message", &sig));
+    assert!(verify_generic(&pk, b"message", &sig2));
+}
+
+#[test]
+fn test_try_sign_bad_key() {
+    let mut rng = ChaChaRng::from_entropy();
+    let keypair = Keypair::<Mode2>::generate(&mut rng);
+
+    // Out of range s1, only the length is checked on this path
+    let mut bytes = keypair.secret().as_bytes().to_vec();
+    bytes[2 * 32 + 48] = 0xff;
+    let sk = SecretKey::<Mode2>::from_bytes_unchecked(&bytes).unwrap();
+
+    assert!(Signer::<Signature<Mode2>>::try_sign(&sk, b"message").is_err());
+    assert!(RandomizedSigner::<Signature<Mode2>>::try_sign_with_rng(&sk, &mut TestRng(rng), b"message").is_err());
+}